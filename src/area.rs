@@ -0,0 +1,261 @@
+use std::f32::consts::PI;
+use std::fmt::Write;
+
+use anyhow::{anyhow, Context, Result};
+use imgui::TreeNodeFlags;
+
+use crate::definitions::*;
+use crate::snapshot::*;
+
+pub const AREA_HEADER: &str = "area";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AreaShape {
+    Rectangle,
+    Disc,
+}
+
+impl AreaShape {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Rectangle => "rectangle",
+            Self::Disc => "disc",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "rectangle" => Some(Self::Rectangle),
+            "disc" => Some(Self::Disc),
+            _ => None,
+        }
+    }
+}
+
+/// Things the area's UI asks for that need the rest of the lights.
+pub enum AreaEvent {
+    Remove,
+    /// Move the area to the camera, facing where it looks.
+    MoveToCamera,
+}
+
+/// A light with a surface, which the engine doesn't have, made out of a grid of point lights.
+/// The area owns those lights (by their id) and keeps them in place, so they move, rotate and
+/// change together. The total intensity gets split evenly between them.
+/// The surface spans the right and up axes of the rotation, facing forward.
+#[derive(Clone)]
+pub struct AreaLight {
+    pub id: usize,
+    pub name: String,
+    pub shape: AreaShape,
+    /// Width and height, or the diameter in the first one for discs.
+    pub size: [f32; 2],
+    pub columns: i32,
+    pub rows: i32,
+    pub color: [f32; 4],
+    pub intensity: f32,
+    /// Radius of each of the point lights.
+    pub radius: f32,
+    pub enabled: bool,
+    pub shadows: bool,
+    pub pos: Position,
+    pub rot: RotationMatrix,
+    pub lights: Vec<usize>,
+}
+
+impl AreaLight {
+    pub fn new(id: usize, pos: Position, rot: RotationMatrix) -> Self {
+        Self {
+            id,
+            name: format!("Area {}", id),
+            shape: AreaShape::Rectangle,
+            size: [1.0, 1.0],
+            columns: 3,
+            rows: 3,
+            color: [1.0; 4],
+            intensity: 3000.0,
+            radius: 4.0,
+            enabled: true,
+            shadows: false,
+            pos,
+            rot,
+            lights: Vec::new(),
+        }
+    }
+
+    /// How many point lights make the area.
+    pub fn count(&self) -> usize {
+        (self.columns.max(1) * self.rows.max(1)) as usize
+    }
+
+    pub fn light_brightness(&self) -> f32 {
+        self.intensity / self.count() as f32
+    }
+
+    /// Where every point light goes, in world space.
+    pub fn positions(&self) -> Vec<Position> {
+        let right = self.rot.right();
+        let up = self.rot.up();
+        let count = self.count();
+
+        match self.shape {
+            AreaShape::Rectangle => {
+                let (columns, rows) = (self.columns.max(1), self.rows.max(1));
+                let mut positions = Vec::with_capacity(count);
+                for row in 0..rows {
+                    for column in 0..columns {
+                        // Every light sits in the middle of its cell.
+                        let x = ((column as f32 + 0.5) / columns as f32 - 0.5) * self.size[0];
+                        let y = ((row as f32 + 0.5) / rows as f32 - 0.5) * self.size[1];
+                        positions.push(self.pos + right * x + up * y);
+                    }
+                }
+                positions
+            }
+            AreaShape::Disc => {
+                // Sunflower pattern, which spreads any amount of points evenly over a disc.
+                let golden_angle = PI * (3.0 - 5.0_f32.sqrt());
+                let radius = self.size[0] * 0.5;
+                (0..count)
+                    .map(|i| {
+                        let r = ((i as f32 + 0.5) / count as f32).sqrt() * radius;
+                        let (sin, cos) = (i as f32 * golden_angle).sin_cos();
+                        self.pos + right * (r * cos) + up * (r * sin)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Appends the area as an `[area]` block, without its lights since the area spawns them.
+    pub fn write(&self, out: &mut String) {
+        let [x, y, z]: [f32; 3] = self.pos.into();
+        let rot: [f32; 12] = self.rot.into();
+        let rot: Vec<String> = rot.iter().map(|v| v.to_string()).collect();
+        let [r, g, b, a] = self.color;
+
+        write_header(out, AREA_HEADER);
+        let _ = writeln!(out, "name = {}", self.name);
+        let _ = writeln!(out, "shape = {}", self.shape.name());
+        let _ = writeln!(out, "size = {} {}", self.size[0], self.size[1]);
+        let _ = writeln!(out, "columns = {}", self.columns);
+        let _ = writeln!(out, "rows = {}", self.rows);
+        let _ = writeln!(out, "color = {} {} {} {}", r, g, b, a);
+        let _ = writeln!(out, "intensity = {}", self.intensity);
+        let _ = writeln!(out, "radius = {}", self.radius);
+        let _ = writeln!(out, "enabled = {}", self.enabled);
+        let _ = writeln!(out, "shadows = {}", self.shadows);
+        let _ = writeln!(out, "position = {} {} {}", x, y, z);
+        let _ = writeln!(out, "rotation = {}", rot.join(" "));
+    }
+
+    /// Builds the area from the fields of an `[area]` block. It gets its id when it's added to
+    /// the rest of the areas.
+    pub fn from_fields(fields: &[(&str, &str)]) -> Result<Self> {
+        let mut pos: Option<Position> = None;
+        let mut rot: Option<RotationMatrix> = None;
+        // Placeholders, the block has to have both.
+        let origin = Position::new(0.0, 0.0, 0.0);
+        let mut area = Self::new(0, origin, RotationMatrix::from_euler(0.0, 0.0, 0.0));
+        for (key, value) in fields {
+            match *key {
+                "name" => area.name = value.to_string(),
+                "shape" => {
+                    area.shape = AreaShape::from_name(value)
+                        .ok_or_else(|| anyhow!("Unknown area shape: {}", value))?
+                }
+                "size" => area.size = parse_array(value)?,
+                "columns" => area.columns = parse::<i32>(value)?.clamp(1, 8),
+                "rows" => area.rows = parse::<i32>(value)?.clamp(1, 8),
+                "color" => area.color = parse_array(value)?,
+                "intensity" => area.intensity = parse(value)?,
+                "radius" => area.radius = parse(value)?,
+                "enabled" => area.enabled = parse(value)?,
+                "shadows" => area.shadows = parse(value)?,
+                "position" => pos = Some(parse_array::<3>(value)?.into()),
+                "rotation" => rot = Some(parse_array::<12>(value)?.into()),
+                _ => (),
+            }
+        }
+
+        area.pos = pos.context("Area without position")?;
+        area.rot = rot.context("Area without rotation")?;
+        Ok(area)
+    }
+
+    pub fn handle_ui(&mut self, ui: &imgui::Ui) -> Option<AreaEvent> {
+        let mut event = None;
+        let id = ui.push_id_usize(self.id);
+        if !ui.collapsing_header(format!("{}###area", self.name), TreeNodeFlags::empty()) {
+            id.end();
+            return None;
+        }
+
+        ui.input_text("Name", &mut self.name).build();
+        ui.radio_button("Rectangle", &mut self.shape, AreaShape::Rectangle);
+        ui.same_line();
+        ui.radio_button("Disc", &mut self.shape, AreaShape::Disc);
+        match self.shape {
+            AreaShape::Rectangle => {
+                imgui::Drag::new("Size")
+                    .range(0.01, 100.0)
+                    .speed(0.01)
+                    .build_array(ui, &mut self.size);
+            }
+            AreaShape::Disc => {
+                imgui::Drag::new("Diameter")
+                    .range(0.01, 100.0)
+                    .speed(0.01)
+                    .build(ui, &mut self.size[0]);
+            }
+        }
+        ui.slider("Columns", 1, 8, &mut self.columns);
+        ui.slider("Rows", 1, 8, &mut self.rows);
+        ui.text(format!("{} point lights", self.count()));
+
+        ui.color_edit4("Color", &mut self.color);
+        imgui::Drag::new("Total intensity")
+            .range(0.1, 1000000.0)
+            .speed(10.0)
+            .build(ui, &mut self.intensity);
+        ui.slider_config("Light radius", 0.1, 50.0)
+            .build(&mut self.radius);
+        ui.checkbox("Enabled", &mut self.enabled);
+        ui.same_line();
+        ui.checkbox("Shadows", &mut self.shadows);
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Every point light casts its own shadows, this gets expensive fast");
+        }
+
+        let mut position: [f32; 3] = self.pos.into();
+        if imgui::Drag::new("Position")
+            .range(f32::MIN, f32::MAX)
+            .speed(0.1)
+            .build_array(ui, &mut position)
+        {
+            self.pos = position.into();
+        }
+
+        let [yaw, pitch, roll] = self.rot.to_euler().map(f32::to_degrees);
+        let mut rotation = [yaw, pitch, roll];
+        if imgui::Drag::new("Yaw, pitch, roll")
+            .range(-180.0, 180.0)
+            .speed(0.5)
+            .build_array(ui, &mut rotation)
+        {
+            let [yaw, pitch, roll] = rotation.map(f32::to_radians);
+            self.rot = RotationMatrix::from_euler(yaw, pitch.clamp(-PI / 2.0, PI / 2.0), roll);
+        }
+
+        if ui.button("Move to camera") {
+            event = Some(AreaEvent::MoveToCamera);
+        }
+        ui.same_line();
+        if ui.button("Remove area") {
+            event = Some(AreaEvent::Remove);
+        }
+
+        id.end();
+        event
+    }
+}
@@ -0,0 +1,229 @@
+use std::f32::consts::TAU;
+
+use crate::definitions::*;
+use crate::math::shift_hue;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ArrayPattern {
+    Line,
+    Grid,
+    Circle,
+    Arc,
+}
+
+impl ArrayPattern {
+    const ALL: [Self; 4] = [Self::Line, Self::Grid, Self::Circle, Self::Arc];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Line => "Line",
+            Self::Grid => "Grid",
+            Self::Circle => "Circle",
+            Self::Arc => "Arc",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ArrayCenter {
+    SourceLight,
+    Player,
+}
+
+/// Where one of the copies goes and how it differs from the source light.
+pub struct ArrayCopy {
+    pub pos: Position,
+    pub rot: RotationMatrix,
+    pub brightness: f32,
+    pub color: [f32; 4],
+}
+
+/// Spawns copies of a light laid out in a pattern, for rows of lanterns or rings of candles.
+/// Every copy can be a bit brighter, have its hue shifted or be rotated a bit more than the
+/// previous one.
+pub struct ArrayTool {
+    pub pattern: ArrayPattern,
+    /// Copies for lines, circles and arcs, columns for grids.
+    pub count: i32,
+    pub rows: i32,
+    /// World space offset between copies in a line. Grids use X and Y as the cell size.
+    pub spacing: [f32; 3],
+    pub center: ArrayCenter,
+    pub radius: f32,
+    /// Degrees covered by the arc.
+    pub arc_angle: f32,
+    /// Makes spotlights in circles and arcs point to the center.
+    pub aim_at_center: bool,
+    pub brightness_step: f32,
+    /// Degrees.
+    pub hue_step: f32,
+    /// Degrees around the world's up axis.
+    pub rotation_step: f32,
+}
+
+impl ArrayTool {
+    pub fn new() -> Self {
+        Self {
+            pattern: ArrayPattern::Line,
+            count: 4,
+            rows: 2,
+            spacing: [2.0, 0.0, 0.0],
+            center: ArrayCenter::SourceLight,
+            radius: 3.0,
+            arc_angle: 90.0,
+            aim_at_center: false,
+            brightness_step: 0.0,
+            hue_step: 0.0,
+            rotation_step: 0.0,
+        }
+    }
+
+    /// Returns true when the user asked to spawn the copies.
+    pub fn handle_ui(&mut self, ui: &imgui::Ui) -> bool {
+        let mut current = ArrayPattern::ALL
+            .iter()
+            .position(|p| *p == self.pattern)
+            .unwrap_or(0);
+        if ui.combo("Pattern", &mut current, &ArrayPattern::ALL, |p| {
+            p.label().into()
+        }) {
+            self.pattern = ArrayPattern::ALL[current];
+        }
+
+        match self.pattern {
+            ArrayPattern::Line => {
+                ui.input_int("Copies", &mut self.count).build();
+                imgui::Drag::new("Offset per copy")
+                    .speed(0.05)
+                    .build_array(ui, &mut self.spacing);
+            }
+            ArrayPattern::Grid => {
+                ui.input_int("Columns", &mut self.count).build();
+                ui.input_int("Rows", &mut self.rows).build();
+                let mut cell = [self.spacing[0], self.spacing[1]];
+                imgui::Drag::new("Cell size")
+                    .speed(0.05)
+                    .build_array(ui, &mut cell);
+                self.spacing[0] = cell[0];
+                self.spacing[1] = cell[1];
+            }
+            ArrayPattern::Circle | ArrayPattern::Arc => {
+                ui.input_int("Copies", &mut self.count).build();
+                ui.radio_button("Around the light", &mut self.center, ArrayCenter::SourceLight);
+                ui.same_line();
+                ui.radio_button("Around the player", &mut self.center, ArrayCenter::Player);
+                ui.slider_config("Radius##array", 0.1, 50.0)
+                    .build(&mut self.radius);
+                if self.pattern == ArrayPattern::Arc {
+                    ui.slider_config("Arc angle", 1.0, 360.0)
+                        .build(&mut self.arc_angle);
+                }
+                ui.checkbox("Aim spotlights at the center", &mut self.aim_at_center);
+            }
+        }
+        self.count = self.count.clamp(1, 100);
+        self.rows = self.rows.clamp(1, 100);
+
+        ui.text("Per copy increments");
+        imgui::Drag::new("Brightness##array_step")
+            .speed(1.0)
+            .build(ui, &mut self.brightness_step);
+        imgui::Drag::new("Hue (deg)")
+            .speed(0.5)
+            .build(ui, &mut self.hue_step);
+        imgui::Drag::new("Rotation (deg)")
+            .speed(0.5)
+            .build(ui, &mut self.rotation_step);
+
+        ui.button("Spawn copies")
+    }
+
+    fn center_for(&self, source: Position, player: Option<Position>) -> Position {
+        match (self.center, player) {
+            (ArrayCenter::Player, Some(player)) => Position::new(player.x, player.y, source.z),
+            _ => source,
+        }
+    }
+
+    fn positions(&self, source: Position, player: Option<Position>) -> Vec<Position> {
+        match self.pattern {
+            ArrayPattern::Line => {
+                let offset: Position = self.spacing.into();
+                (1..=self.count).map(|k| source + offset * (k as f32)).collect()
+            }
+            ArrayPattern::Grid => {
+                let (dx, dy) = (self.spacing[0], self.spacing[1]);
+                (0..self.rows)
+                    .flat_map(|row| (0..self.count).map(move |col| (row, col)))
+                    .filter(|&cell| cell != (0, 0))
+                    .map(|(row, col)| {
+                        source + Position::new(col as f32 * dx, row as f32 * dy, 0.0)
+                    })
+                    .collect()
+            }
+            ArrayPattern::Circle | ArrayPattern::Arc => {
+                let center = self.center_for(source, player);
+
+                // When the source light isn't in the center, the circle starts from it so it's
+                // part of the pattern.
+                let to_source = source - center;
+                let (start, first) = if to_source.length() > 1e-3 {
+                    (to_source.y.atan2(to_source.x), 1)
+                } else {
+                    (0.0, 0)
+                };
+                let step = match self.pattern {
+                    ArrayPattern::Circle => TAU / (self.count + first) as f32,
+                    // Both ends of the arc get a light, which is the source one if it's on it.
+                    _ => self.arc_angle.to_radians() / (self.count + first - 1).max(1) as f32,
+                };
+
+                (first..self.count + first)
+                    .map(|k| {
+                        let angle = start + step * k as f32;
+                        center
+                            + Position::new(angle.cos(), angle.sin(), 0.0) * self.radius
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Computes every copy of the source light.
+    pub fn copies(
+        &self,
+        source: (Position, RotationMatrix),
+        brightness: f32,
+        color: [f32; 4],
+        player: Option<Position>,
+    ) -> Vec<ArrayCopy> {
+        let (source_pos, source_rot) = source;
+        let center = self.center_for(source_pos, player);
+        let aims = self.aim_at_center
+            && matches!(self.pattern, ArrayPattern::Circle | ArrayPattern::Arc);
+
+        self.positions(source_pos, player)
+            .into_iter()
+            .enumerate()
+            .map(|(i, pos)| {
+                let k = (i + 1) as f32;
+                let rot = if aims {
+                    RotationMatrix::look_at(center - pos)
+                } else {
+                    source_rot
+                };
+                let rot = rot.rotated(
+                    Position::new(0.0, 0.0, 1.0),
+                    (self.rotation_step * k).to_radians(),
+                );
+
+                ArrayCopy {
+                    pos,
+                    rot,
+                    brightness: (brightness + self.brightness_step * k).max(0.1),
+                    color: shift_hue(color, self.hue_step * k),
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,131 @@
+use crate::definitions::*;
+use crate::fields::{field, FieldId};
+
+/// Edits brightness, radius and color of every selected light at once. In absolute mode the
+/// values are set as-is on every light, in relative mode the brightness and radius get scaled and
+/// the color gets shifted, keeping the differences between the lights.
+pub struct BatchEdit {
+    pub relative: bool,
+    brightness: f32,
+    radius: f32,
+    color: [f32; 4],
+}
+
+// Neutral values for the relative mode, i.e., the ones that don't change anything.
+const NEUTRAL_FACTOR: f32 = 1.0;
+const NEUTRAL_SHIFT: [f32; 4] = [0.5, 0.5, 0.5, 1.0];
+
+impl BatchEdit {
+    pub fn new() -> Self {
+        Self {
+            relative: false,
+            brightness: 1000.0,
+            radius: 5.0,
+            color: [1.; 4],
+        }
+    }
+
+    fn reset_relative(&mut self) {
+        self.brightness = NEUTRAL_FACTOR;
+        self.radius = NEUTRAL_FACTOR;
+        self.color = NEUTRAL_SHIFT;
+    }
+
+    pub fn handle_ui(&mut self, ui: &imgui::Ui, lights: &mut [LightContainer]) {
+        let selected = lights.iter().filter(|l| l.selected).count();
+        if selected == 0 {
+            return;
+        }
+
+        ui.text(format!("Batch edit ({} selected)", selected));
+        if ui.radio_button_bool("Absolute", !self.relative) {
+            *self = Self::new();
+        }
+        ui.same_line();
+        if ui.radio_button_bool("Relative", self.relative) {
+            self.relative = true;
+            self.reset_relative();
+        }
+
+        let selected = lights.iter_mut().filter(|l| l.selected);
+        if self.relative {
+            self.relative_ui(ui, selected.collect());
+        } else {
+            self.absolute_ui(ui, selected.collect());
+        }
+        ui.separator();
+    }
+
+    fn absolute_ui(&mut self, ui: &imgui::Ui, mut lights: Vec<&mut LightContainer>) {
+        if imgui::Drag::new("Brightness##batch")
+            .range(0.1, 100000.0)
+            .speed(1.0)
+            .build(ui, &mut self.brightness)
+        {
+            let brightness = field(FieldId::Brightness);
+            lights.iter_mut().for_each(|l| brightness.write(l, self.brightness));
+        }
+
+        if ui
+            .slider_config("Radius##batch", 0.1, 180.0)
+            .build(&mut self.radius)
+        {
+            let radius = field(FieldId::Radius);
+            lights.iter_mut().for_each(|l| radius.write(l, self.radius));
+        }
+
+        if ui.color_edit4("Color##batch", &mut self.color) {
+            lights.iter_mut().for_each(|l| l.color = self.color);
+        }
+    }
+
+    fn relative_ui(&mut self, ui: &imgui::Ui, mut lights: Vec<&mut LightContainer>) {
+        let previous = self.brightness;
+        if imgui::Drag::new("Brightness x##batch")
+            .range(0.01, 100.0)
+            .speed(0.01)
+            .build(ui, &mut self.brightness)
+        {
+            let factor = self.brightness / previous;
+            let brightness = field(FieldId::Brightness);
+            lights.iter_mut().for_each(|l| {
+                let value = brightness.read(l) * factor;
+                brightness.write(l, value);
+            });
+        }
+        if ui.is_item_deactivated() {
+            self.brightness = NEUTRAL_FACTOR;
+        }
+
+        let previous = self.radius;
+        if imgui::Drag::new("Radius x##batch")
+            .range(0.01, 100.0)
+            .speed(0.01)
+            .build(ui, &mut self.radius)
+        {
+            let factor = self.radius / previous;
+            let radius = field(FieldId::Radius);
+            lights.iter_mut().for_each(|l| {
+                let value = radius.read(l) * factor;
+                radius.write(l, value);
+            });
+        }
+        if ui.is_item_deactivated() {
+            self.radius = NEUTRAL_FACTOR;
+        }
+
+        let previous = self.color;
+        if ui.color_edit4("Color shift##batch", &mut self.color) {
+            lights.iter_mut().for_each(|l| {
+                let shifts = self.color.iter().zip(previous.iter());
+                for (c, (new, old)) in l.color.iter_mut().zip(shifts) {
+                    *c = (*c + new - old).clamp(0.0, 1.0);
+                }
+            });
+        }
+        ui.same_line();
+        if ui.small_button("Reset##batch") {
+            self.reset_relative();
+        }
+    }
+}
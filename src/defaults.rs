@@ -0,0 +1,97 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use imgui::TreeNodeFlags;
+
+use crate::definitions::*;
+use crate::snapshot::*;
+
+pub const DEFAULTS_FILE: &str = "litcher_defaults.txt";
+
+pub const DEFAULTS_HEADER: &str = "defaults";
+
+/// The settings every new light starts with, one set per light kind. Lights spawned from a
+/// preset, a scene or a copy get these first and then the settings they came with.
+pub struct SpawnDefaults {
+    pub spotlight: LightSnapshot,
+    pub pointlight: LightSnapshot,
+    status: String,
+}
+
+impl SpawnDefaults {
+    /// Reads the defaults file, falling back to the built-in values if it doesn't exist yet.
+    pub fn new() -> Self {
+        let (snapshots, status) = load_or(DEFAULTS_FILE, Self::parse, Vec::new);
+        let mut defaults = Self {
+            spotlight: LightSnapshot::new(LightKind::SpotLight),
+            pointlight: LightSnapshot::new(LightKind::PointLight),
+            status,
+        };
+        for snapshot in snapshots {
+            *defaults.get_mut(snapshot.kind) = snapshot;
+        }
+
+        defaults
+    }
+
+    pub fn get(&self, kind: LightKind) -> &LightSnapshot {
+        match kind {
+            LightKind::SpotLight => &self.spotlight,
+            LightKind::PointLight => &self.pointlight,
+        }
+    }
+
+    fn get_mut(&mut self, kind: LightKind) -> &mut LightSnapshot {
+        match kind {
+            LightKind::SpotLight => &mut self.spotlight,
+            LightKind::PointLight => &mut self.pointlight,
+        }
+    }
+
+    fn parse(text: &str) -> Result<Vec<LightSnapshot>> {
+        parse_blocks(text)?
+            .iter()
+            .filter(|block| block.name == DEFAULTS_HEADER)
+            .map(|block| LightSnapshot::from_fields(&block.fields))
+            .collect()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let mut out = String::new();
+        for snapshot in [&self.spotlight, &self.pointlight] {
+            write_header(&mut out, DEFAULTS_HEADER);
+            snapshot.write_fields(&mut out);
+            let _ = writeln!(out);
+        }
+
+        save_data(DEFAULTS_FILE, &out)
+    }
+
+    pub fn handle_ui(&mut self, ui: &imgui::Ui) {
+        for kind in [LightKind::SpotLight, LightKind::PointLight] {
+            let _id = ui.push_id(kind.name());
+            if ui.collapsing_header(
+                format!("New {} defaults", kind.name()),
+                TreeNodeFlags::empty(),
+            ) {
+                self.get_mut(kind).render_ui(ui);
+            }
+        }
+
+        if ui.button("Save defaults") {
+            self.status = match self.save() {
+                Ok(()) => format!("Saved to {}", DEFAULTS_FILE),
+                Err(e) => format!("Error: {:#}", e),
+            };
+        }
+        ui.same_line();
+        if ui.button("Reset defaults") {
+            self.spotlight = LightSnapshot::new(LightKind::SpotLight);
+            self.pointlight = LightSnapshot::new(LightKind::PointLight);
+        }
+
+        if !self.status.is_empty() {
+            ui.text_wrapped(&self.status);
+        }
+    }
+}
@@ -1,9 +1,11 @@
 use std::marker::PhantomData;
 
+use crate::fields::fields_ui;
+use crate::mixer::MasterControls;
 use crate::pointer::*;
-use imgui::Condition;
+use crate::snapshot::LightSnapshot;
+use imgui::{ColorEditFlags, Condition, DragDropFlags};
 use lazy_re::lazy_re;
-use windows_sys::Win32::UI::Input::KeyboardAndMouse::VK_SHIFT;
 
 #[repr(C, packed)]
 #[derive(Copy, Clone, Debug)]
@@ -13,9 +15,8 @@ pub struct Position {
     pub z: f32,
 }
 
-/// For this game, we actually don't care which values are in here since we'll only copy/clone
-/// those values from one struct to another, so this abstraction without any impl is more than
-/// enough.
+/// Three rows of four floats, the fourth one of each row being padding. The rows are the right,
+/// forward and up axes of the object in world space, see `math.rs` for the helpers built on that.
 #[repr(C, packed)]
 #[derive(Debug, Copy, Clone)]
 pub struct RotationMatrix([f32; 4 * 3]);
@@ -55,33 +56,163 @@ impl LightType {
             Self::PointLight(PointLight { light, .. }) => light,
         }
     }
+
+    pub fn kind(&self) -> LightKind {
+        match self {
+            Self::SpotLight(_) => LightKind::SpotLight,
+            Self::PointLight(_) => LightKind::PointLight,
+        }
+    }
+
+    /// Pointer to the start of the light, for the fields we access by their offset.
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        match self {
+            Self::SpotLight(l) => &mut **l as *mut SpotLight as *mut u8,
+            Self::PointLight(l) => &mut **l as *mut PointLight as *mut u8,
+        }
+    }
+}
+
+/// The type of a light without the pointer to it, useful for whenever we need to know what to
+/// spawn before having the light itself.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LightKind {
+    SpotLight,
+    PointLight,
+}
+
+impl LightKind {
+    pub const ALL: [Self; 2] = [Self::SpotLight, Self::PointLight];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SpotLight => "spotlight",
+            Self::PointLight => "pointlight",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "spotlight" => Some(Self::SpotLight),
+            "pointlight" => Some(Self::PointLight),
+            _ => None,
+        }
+    }
+}
+
+/// Things the light's window asks for that it can't do by itself, since they need the rest of
+/// the lights or the memory pools.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LightAction {
+    Duplicate,
+    Copy,
+    Paste,
+    Remove,
+    /// The user interacted with this light, so it becomes the last selected one.
+    Select,
+    /// The light with the given id got dragged and dropped on this one.
+    MoveHere(usize),
 }
 
+/// While previewing the shadow cutoff, the fade distance gets scaled by this so the point where
+/// shadows disappear can be seen without moving the camera away.
+pub const SHADOW_PREVIEW_SCALE: f32 = 0.2;
+
+/// Drag and drop payload name for reordering the lights in the list. The payload is the light's
+/// id, since its index can change while it's being dragged.
+pub const LIGHT_PAYLOAD: &str = "LIGHT_ROW";
+
+/// Labels for the values of `LightEntity::shadow_casting_mode`.
+pub const SHADOWS_OPTIONS: [&str; 3] = [
+    "0 - No shadows",
+    "1 - Characters and objects",
+    "2 - Characters only",
+];
+
 /// This struct will contain the light pointer that's created inside the game's memory alongside
 /// with some external parameters we need for the UI/Control. We need to have an own copy of the
 /// color for imgui to work properly.
-/// Every LightContainer should have an unique id since imgui uses it as unique tokens.
+/// The color, brightness, radius, shadow fade distance and enabled state stored here are the
+/// light's own values, the ones that get written to the game are these after going through the
+/// `MasterControls` and the shadow preview.
+/// Every LightContainer should have an unique id since imgui uses it as unique tokens. The name
+/// is only what we show, so it can be changed freely.
 pub struct LightContainer {
     pub light: LightType,
 
     // our settings
     pub attach_camera: bool,
     pub color: [f32; 4],
+    pub brightness: f32,
+    pub radius: f32,
+    pub enabled: bool,
+    pub shadow_fade_distance: f32,
+    pub shadow_preview: bool,
+    pub solo: bool,
+    pub mute: bool,
+    pub group: Option<usize>,
+    /// The area light this light is part of, if any. Those are driven by the area.
+    pub area: Option<usize>,
+    pub selected: bool,
     pub open: bool,
-    pub id: String,
+    /// Whether the light's window was focused last frame, so focusing it selects the light once.
+    focused: bool,
+    pub id: usize,
+    pub name: String,
 }
 
 impl LightContainer {
-    pub fn new(light: LightType, id: usize) -> Self {
+    pub fn new(mut light: LightType, id: usize) -> Self {
+        let inner_light = light.get_light_mut();
+        let brightness = inner_light.light_settings.brightness;
+        let radius = inner_light.light_settings.radius;
+        let enabled = inner_light.is_enabled;
+        let color = inner_light.light_settings.color.into();
+        let shadow_fade_distance = inner_light.shadow_fade_distance;
+
         Self {
             light,
             attach_camera: false,
-            color: [1.; 4],
+            color,
+            brightness,
+            radius,
+            enabled,
+            shadow_fade_distance,
+            shadow_preview: false,
+            solo: false,
+            mute: false,
+            group: None,
+            area: None,
+            selected: false,
             open: true,
-            id: format!("Light {}", id),
+            focused: false,
+            id,
+            name: format!("Light {}", id),
         }
     }
 
+    /// Writes our own values to the game's light, scaled by the master controls. If any light in
+    /// the scene is soloed, only the soloed ones stay lit.
+    pub fn apply_master(&mut self, master: &MasterControls, any_solo: bool, group_enabled: bool) {
+        let audible = group_enabled && !self.mute && (!any_solo || self.solo);
+        let color = master.apply_color(self.color);
+        let brightness = master.apply_brightness(self.brightness);
+        let radius = master.apply_radius(self.radius);
+        let enabled = self.enabled && audible;
+        let shadow_fade_distance = if self.shadow_preview {
+            self.shadow_fade_distance * SHADOW_PREVIEW_SCALE
+        } else {
+            self.shadow_fade_distance
+        };
+
+        let light = self.light.get_light_mut();
+        light.light_settings.color = color.into();
+        light.light_settings.brightness = brightness;
+        light.light_settings.radius = radius;
+        light.is_enabled = enabled;
+        light.shadow_fade_distance = shadow_fade_distance;
+    }
+
     /// LightContainer::update_render needs to be called for every render loop to update all
     /// parameters of the lights. We could technically optimize this to only be called when
     /// something on imgui changes but we don't care for that right now.
@@ -103,6 +234,11 @@ impl LightContainer {
         self.update_render(world);
     }
 
+    pub fn get_pos_rot(&mut self) -> (Position, RotationMatrix) {
+        let entity = &self.light.get_light_mut().entity;
+        (entity.pos, entity.rot_matrix)
+    }
+
     pub fn set_pos_rot(&mut self, pos: Position, rot: RotationMatrix) {
         match &mut self.light {
             LightType::PointLight(pl) => {
@@ -114,78 +250,164 @@ impl LightContainer {
         };
     }
 
-    pub fn render_window(&mut self, ui: &mut imgui::Ui) {
+    /// Case insensitive search on the light's name.
+    pub fn matches_filter(&self, filter: &str) -> bool {
+        filter.is_empty() || self.name.to_lowercase().contains(&filter.to_lowercase())
+    }
+
+    /// Draws the light's row in the main window list.
+    pub fn render_row(&mut self, ui: &imgui::Ui) -> Option<LightAction> {
+        let mut action = None;
+        let id = ui.push_id_usize(self.id);
+        if ui.button("X") {
+            action = Some(LightAction::Remove);
+        }
+        ui.same_line();
+        if ui
+            .selectable_config(format!("{}##name", self.name))
+            .selected(self.selected)
+            .size([100.0, 0.0])
+            .build()
+        {
+            self.selected = !self.selected;
+            action = Some(LightAction::Select);
+        }
+
+        if let Some(tooltip) = ui.drag_drop_source_config(LIGHT_PAYLOAD).begin_payload(self.id) {
+            ui.text(&self.name);
+            tooltip.end();
+        }
+
+        if let Some(target) = ui.drag_drop_target() {
+            if let Some(Ok(payload)) =
+                target.accept_payload::<usize, _>(LIGHT_PAYLOAD, DragDropFlags::empty())
+            {
+                action = Some(LightAction::MoveHere(payload.data));
+            }
+            target.pop();
+        }
+
+        ui.same_line();
+        ui.color_button_config("Color of light ##", self.color)
+            .flags(ColorEditFlags::NO_INPUTS | ColorEditFlags::NO_LABEL)
+            .build();
+        ui.same_line();
+        if ui.button("Edit") {
+            self.open = true;
+            action = Some(LightAction::Select);
+        }
+        ui.same_line();
+        if ui.small_button("Dup") {
+            action = Some(LightAction::Duplicate);
+        }
+        ui.same_line();
+        if ui.small_button("Copy") {
+            action = Some(LightAction::Copy);
+        }
+        ui.same_line();
+        if ui.small_button("Paste") {
+            action = Some(LightAction::Paste);
+        }
+        ui.same_line();
+
+        ui.checkbox("on/off", &mut self.enabled);
+        ui.same_line();
+        ui.checkbox("S", &mut self.solo);
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Solo");
+        }
+        ui.same_line();
+        ui.checkbox("M", &mut self.mute);
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Mute");
+        }
+
+        ui.same_line();
+        ui.checkbox("Attach to camera", &mut self.attach_camera);
+
+        id.end();
+        action
+    }
+
+    /// `camera` is the camera's rotation, if there's a camera, to label things relative to it.
+    pub fn render_window(
+        &mut self,
+        ui: &mut imgui::Ui,
+        camera: Option<RotationMatrix>,
+    ) -> Option<LightAction> {
         if !self.open {
-            return;
+            return None;
         }
 
-        ui.window(&self.id)
+        let mut action = None;
+        let mut open = self.open;
+        // Cleared so it stays false if the window doesn't get drawn, e.g. when it's collapsed.
+        let was_focused = std::mem::take(&mut self.focused);
+        ui.window(format!("{}###light{}", self.name, self.id))
             .size([350.0, 510.0], Condition::FirstUseEver)
-            .opened(&mut self.open)
+            .opened(&mut open)
             .build(|| {
+                self.focused = ui.is_window_focused();
+                if self.focused && !was_focused {
+                    action = Some(LightAction::Select);
+                }
+
+                ui.input_text("Name", &mut self.name).build();
+
                 let light = self.light.get_light_mut();
                 // TODO: Revisit this!
                 ui.color_picker4("color picker", &mut self.color);
-                light.light_settings.color = self.color.into();
-                let mut brightness = light.light_settings.brightness;
-                let mut radius = light.light_settings.radius;
-                let mut attenuation = light.light_settings.attenuation;
-                let mut casting_mode = light.shadow_casting_mode as usize;
                 let mut position: [f32; 3] = light.entity.pos.into();
-                let mut shadow_blend_factor = light.shadow_blend_factor;
 
                 imgui::Drag::new("Position")
                     .range(f32::MIN, f32::MAX)
                     .speed(0.1)
                     .build_array(ui, &mut position);
+                light.entity.pos = position.into();
 
-                imgui::Drag::new("Brightness")
-                    .range(0.1, 100000.0)
-                    .speed(if ui.is_key_index_down(VK_SHIFT as _) {
-                        20.0
-                    } else {
-                        1.0
-                    })
-                    .build(ui, &mut brightness);
-
-                ui.slider_config("Radius", f32::MIN, f32::MAX)
-                    .range(0.1, 180.0)
-                    .build(&mut radius);
-
-                ui.slider_config("Shadow blend", f32::MIN, f32::MAX)
-                    .range(0.0001, 1.0)
-                    .build(&mut shadow_blend_factor);
-
-                ui.slider_config("Attenuation", f32::MIN, f32::MAX)
-                    .range(0.0001, 1.0)
-                    .build(&mut attenuation);
-
-                const SHADOWS_OPTIONS: [&'static str; 3] = [
-                    "0 - No shadows",
-                    "1 - Characters and objects",
-                    "2 - Characters only",
-                ];
-                ui.combo("Shadow cast", &mut casting_mode, &[0, 1, 2], |&i| {
-                    SHADOWS_OPTIONS[i].into()
-                });
-
-                ui.checkbox("Is enabled", &mut light.is_enabled);
+                fields_ui(ui, self, true, camera);
+                ui.checkbox("Preview shadow cutoff", &mut self.shadow_preview);
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(format!(
+                        "Temporarily brings the fade distance down to {}% of its value",
+                        SHADOW_PREVIEW_SCALE * 100.0
+                    ));
+                }
+
+                ui.checkbox("Is enabled", &mut self.enabled);
+                ui.same_line();
+                ui.checkbox("Solo", &mut self.solo);
+                ui.same_line();
+                ui.checkbox("Mute", &mut self.mute);
                 ui.checkbox("Attach to camera", &mut self.attach_camera);
 
-                light.entity.pos = position.into();
-                light.light_settings.brightness = brightness;
-                light.light_settings.radius = radius;
-                light.light_settings.attenuation = attenuation;
-                light.shadow_casting_mode = casting_mode as _;
-                light.shadow_blend_factor = shadow_blend_factor;
-
                 ui.separator();
 
-                match &mut self.light {
-                    LightType::PointLight(pl) => pl.render_ui(ui),
-                    LightType::SpotLight(spl) => spl.render_ui(ui),
+                match self.light.kind() {
+                    LightKind::PointLight => ui.text("Pointlight specific"),
+                    LightKind::SpotLight => ui.text("Spotlight specific"),
                 };
+                fields_ui(ui, self, false, camera);
+                if let LightType::SpotLight(spl) = &mut self.light {
+                    spl.fix_angles();
+                }
+
+                ui.separator();
+                if ui.button("Duplicate") {
+                    action = Some(LightAction::Duplicate);
+                }
+                ui.same_line();
+                if ui.button("Copy settings") {
+                    action = Some(LightAction::Copy);
+                }
+                ui.same_line();
+                if ui.button("Paste settings") {
+                    action = Some(LightAction::Paste);
+                }
             });
+        self.open = open;
+
+        action
     }
 }
 
@@ -277,6 +499,7 @@ impl SpotLight {
         memory_pool: &'static mut MemoryPool<Self>,
         position: Position,
         rot: RotationMatrix,
+        defaults: &LightSnapshot,
         world: usize,
     ) -> &'static mut Self {
         let light_ptr = memory_pool.new_light();
@@ -284,13 +507,9 @@ impl SpotLight {
         light_ptr.light.entity.pos = position;
         light_ptr.light.entity.rot_matrix = rot;
 
-        light_ptr.light.light_settings.brightness = 1000.0;
-        light_ptr.light.light_settings.radius = 5.0;
-        light_ptr.inner_angle = 30.0;
-        light_ptr.outer_angle = 45.0;
-        light_ptr.softness = 2.;
-
-        light_ptr.light.shadow_casting_mode = 1;
+        light_ptr.light.light_settings.color = defaults.color.into();
+        unsafe { defaults.write_memory(light_ptr as *mut Self as *mut u8) };
+        light_ptr.fix_angles();
         light_ptr.light.is_enabled = true;
 
         unsafe { (light_ptr.light.entity.vt.set_flags)(&mut light_ptr.light, world) };
@@ -304,30 +523,11 @@ impl SpotLight {
         self.light.entity.rot_matrix = rot;
     }
 
-    pub fn render_ui(&mut self, ui: &imgui::Ui) {
-        ui.text("Spotlight specific");
-        let mut inner_angle = self.inner_angle;
-        let mut outer_angle = self.outer_angle;
-        let mut softness = self.softness;
-
-        ui.slider_config("Inner angle", f32::MIN, f32::MAX)
-            .range(0.1, outer_angle - 1.0)
-            .build(&mut inner_angle);
-
-        ui.slider_config("Outer angle", f32::MIN, f32::MAX)
-            .range(0.1, 180.0)
-            .build(&mut outer_angle);
-
-        ui.slider_config("Softness", f32::MIN, f32::MAX)
-            .range(0.1, 100.0)
-            .build(&mut softness);
-
-        self.outer_angle = outer_angle;
-        self.inner_angle = inner_angle;
+    /// The inner angle has to stay smaller than the outer one.
+    pub fn fix_angles(&mut self) {
         if self.outer_angle < self.inner_angle {
             self.inner_angle = self.outer_angle - 1.;
         }
-        self.softness = softness;
     }
 
     pub fn update_render(&mut self, world: usize) {
@@ -345,11 +545,80 @@ pub struct PointLight {
     pub dynamic_shadow_face_mask: u8,
 }
 
+/// The faces of the point light's shadow cube, in the order of the bits of
+/// `dynamic_shadow_face_mask`, which follows the usual cube map face order.
+pub const SHADOW_FACES: [(&str, [f32; 3]); 6] = [
+    ("+X", [1.0, 0.0, 0.0]),
+    ("-X", [-1.0, 0.0, 0.0]),
+    ("+Y", [0.0, 1.0, 0.0]),
+    ("-Y", [0.0, -1.0, 0.0]),
+    ("+Z", [0.0, 0.0, 1.0]),
+    ("-Z", [0.0, 0.0, -1.0]),
+];
+
+/// Where a face of the shadow cube points to as seen from the camera.
+fn camera_side(normal: Position, camera: RotationMatrix) -> &'static str {
+    let sides = [
+        (camera.right(), "right", "left"),
+        (camera.forward(), "front", "behind"),
+        (camera.up(), "up", "down"),
+    ];
+
+    sides
+        .iter()
+        .map(|&(axis, positive, negative)| {
+            let dot = normal.dot(axis);
+            (dot.abs(), if dot >= 0.0 { positive } else { negative })
+        })
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, side)| side)
+        .unwrap_or("")
+}
+
+/// One checkbox per face of the shadow cube. When we have the camera, each face also says where
+/// it points to relative to it.
+/// Returns true when the mask changed.
+pub fn shadow_face_mask_ui(ui: &imgui::Ui, mask: &mut u8, camera: Option<RotationMatrix>) -> bool {
+    let before = *mask;
+    ui.text("Dynamic shadow faces");
+    for (i, (name, normal)) in SHADOW_FACES.iter().enumerate() {
+        let label = match camera {
+            Some(camera) => format!(
+                "{} ({})##face{}",
+                name,
+                camera_side((*normal).into(), camera),
+                i
+            ),
+            None => format!("{}##face{}", name, i),
+        };
+
+        let bit = 1 << i;
+        let mut enabled = *mask & bit != 0;
+        if i % 2 == 1 {
+            ui.same_line_with_pos(140.0);
+        }
+        if ui.checkbox(label, &mut enabled) {
+            *mask ^= bit;
+        }
+    }
+
+    if ui.small_button("All faces") {
+        *mask = 0x3F;
+    }
+    ui.same_line();
+    if ui.small_button("No faces") {
+        *mask = 0;
+    }
+
+    *mask != before
+}
+
 impl PointLight {
     pub fn new(
         memory_pool: &'static mut MemoryPool<Self>,
         position: Position,
         rot: RotationMatrix,
+        defaults: &LightSnapshot,
         world: usize,
     ) -> &'static mut Self {
         let light_ptr = memory_pool.new_light();
@@ -357,13 +626,8 @@ impl PointLight {
         light_ptr.light.entity.pos = position;
         light_ptr.light.entity.rot_matrix = rot;
 
-        light_ptr.light.light_settings.brightness = 1000.0;
-        light_ptr.light.light_settings.radius = 5.0;
-
-        light_ptr.cache_static_shadows = 1;
-        light_ptr.dynamic_shadow_face_mask = 1;
-
-        light_ptr.light.shadow_casting_mode = 1;
+        light_ptr.light.light_settings.color = defaults.color.into();
+        unsafe { defaults.write_memory(light_ptr as *mut Self as *mut u8) };
         light_ptr.light.is_enabled = true;
 
         unsafe { (light_ptr.light.entity.vt.set_flags)(&mut light_ptr.light, world) };
@@ -377,18 +641,6 @@ impl PointLight {
         self.light.entity.rot_matrix = rot;
     }
 
-    pub fn render_ui(&mut self, ui: &imgui::Ui) {
-        ui.text("Pointlight specific");
-        let mut cache_static_shadows: bool = self.cache_static_shadows != 0;
-        let mut dynamic_shadow_face_mask: bool = self.dynamic_shadow_face_mask != 0;
-
-        ui.checkbox("Cache static shadows", &mut cache_static_shadows);
-        ui.checkbox("Dynamic Shadow Face Mask", &mut dynamic_shadow_face_mask);
-
-        self.cache_static_shadows = cache_static_shadows as _;
-        self.dynamic_shadow_face_mask = (dynamic_shadow_face_mask as u8) * 0x3F;
-    }
-
     pub fn update_render(&mut self, world: usize) {
         unsafe { (self.light.entity.vt.set_flags)(&mut self.light, world) };
     }
@@ -463,6 +715,11 @@ impl CR4Player {
         Some(world)
     }
 
+    pub fn get_position(&self) -> Option<Position> {
+        let player = unsafe { self.0.read()? };
+        Some(player.pos)
+    }
+
     pub fn get_camera(&self) -> Option<&'static CR4CameraDirector> {
         let layer = unsafe { self.0.read()?.ptr00? };
         let world = layer.ptr01?;
@@ -528,3 +785,15 @@ impl From<Position> for [f32; 3] {
         [pos.x, pos.y, pos.z]
     }
 }
+
+impl From<[f32; 12]> for RotationMatrix {
+    fn from(arr: [f32; 12]) -> Self {
+        RotationMatrix(arr)
+    }
+}
+
+impl From<RotationMatrix> for [f32; 12] {
+    fn from(rot: RotationMatrix) -> Self {
+        rot.0
+    }
+}
@@ -1,51 +1,341 @@
+use std::fmt::Write;
+
+use anyhow::Result;
 use lazy_re::lazy_re;
 use memory_rs::generate_aob_pattern;
-use memory_rs::internal::injections::{Inject, Detour};
 use memory_rs::internal::process_info::ProcessInfo;
 
-memory_rs::scoped_no_mangle! {
-    overwrite_tonemapping_jmb: usize = 0x0;
-    overwrite_tonemapping_val: f32 = 1.0;
-    overwrite_tonemapping_enable: u8 = 0x0;
-}
+use crate::hooks::*;
+use crate::snapshot::*;
+
+/// Read by `overwrite_tonemapping`, the exposure goes in the first value.
+#[no_mangle]
+#[allow(non_upper_case_globals)]
+static mut tonemapping_params: HookParams = HookParams::new();
 
 extern "C" {
     pub static overwrite_tonemapping: u8;
 }
 
+pub const EXPOSURE_HOOK: &str = "Exposure";
+
+const MIN_EXPOSURE: f32 = 1e-6;
+const MAX_EXPOSURE: f32 = 3.0;
+
+/// How much the exposure keys change the exposure, in stops.
+const EV_STEP: f32 = 1.0 / 3.0;
+pub const EXPOSURE_UP_KEY: imgui::Key = imgui::Key::PageUp;
+pub const EXPOSURE_DOWN_KEY: imgui::Key = imgui::Key::PageDown;
+
+pub const EXPOSURE_PRESETS_FILE: &str = "litcher_exposure.txt";
+pub const EXPOSURE_PRESET_HEADER: &str = "exposure_preset";
+pub const EXPOSURE_HEADER: &str = "exposure";
+
+/// What gets stored in a scene.
+#[derive(Copy, Clone, Debug)]
+pub struct ExposureSettings {
+    pub value: f32,
+    pub overwrite: bool,
+}
+
+impl ExposureSettings {
+    pub fn write(&self, out: &mut String) {
+        write_header(out, EXPOSURE_HEADER);
+        let _ = writeln!(out, "value = {}", self.value);
+        let _ = writeln!(out, "overwrite = {}", self.overwrite);
+    }
+
+    pub fn from_fields(fields: &[(&str, &str)]) -> Result<Self> {
+        let mut settings = Self {
+            value: 1.0,
+            overwrite: true,
+        };
+        for (key, value) in fields {
+            match *key {
+                "value" => {
+                    settings.value = parse::<f32>(value)?.clamp(MIN_EXPOSURE, MAX_EXPOSURE)
+                }
+                "overwrite" => settings.overwrite = parse(value)?,
+                _ => (),
+            }
+        }
+
+        Ok(settings)
+    }
+}
+
+pub struct ExposurePreset {
+    pub name: String,
+    pub value: f32,
+}
+
+fn starter_exposure_presets() -> Vec<ExposurePreset> {
+    [
+        ("Neutral", 1.0),
+        ("Dim (-1 EV)", 0.5),
+        ("Dusk (-2 EV)", 0.25),
+        ("Night (-4 EV)", 0.0625),
+    ]
+    .into_iter()
+    .map(|(name, value)| ExposurePreset {
+        name: name.to_string(),
+        value,
+    })
+    .collect()
+}
+
+/// A fade from one exposure to another. It goes linearly in stops, which is how the eye sees
+/// it, so it doesn't look like it's rushing through the dark part.
+struct Transition {
+    from_ev: f32,
+    to_ev: f32,
+    elapsed: f32,
+}
+
 pub struct ToneMappingContainer {
-    detour: Detour,
+    /// Whether we found the code to hook, the override does nothing otherwise.
+    available: bool,
     value: f32,
-    overwrite: bool
+    overwrite: bool,
+    /// Seconds it takes to go to a new exposure, 0 to change it right away.
+    transition_time: f32,
+    transition: Option<Transition>,
+    presets: Vec<ExposurePreset>,
+    current_preset: usize,
+    new_preset_name: String,
+    status: String,
+}
+
+fn ev(value: f32) -> f32 {
+    value.log2()
+}
+
+fn from_ev(ev: f32) -> f32 {
+    ev.exp2().clamp(MIN_EXPOSURE, MAX_EXPOSURE)
 }
 
 impl ToneMappingContainer {
-    pub fn new(proc_info: &ProcessInfo) -> Self {
+    pub fn new(proc_info: &ProcessInfo, hooks: &mut HookManager) -> Self {
         let mp = generate_aob_pattern![0x48, 0x85, 0xD2, 0x74, 0x10, 0x4C, 0x39, 0x72, 0x08, 0x74, 0x0A, 0x48, 0x8B, 0x52, 0x08];
 
-        let addr = proc_info.region.scan_aob(&mp).unwrap().unwrap();
+        let hook = HookDefinition {
+            name: EXPOSURE_HOOK,
+            pattern: mp,
+            offset: 0,
+            size: 32,
+            stub: unsafe { &raw const overwrite_tonemapping as usize },
+            params: &raw mut tonemapping_params,
+        };
+        let (presets, mut status) = load_or(
+            EXPOSURE_PRESETS_FILE,
+            Self::parse_presets,
+            starter_exposure_presets,
+        );
 
-        let mut detour = unsafe {
-            Detour::new(addr, 32, &raw const overwrite_tonemapping as usize, Some(&mut overwrite_tonemapping_jmb))
+        let available = match hooks.register(proc_info, hook) {
+            Ok(()) => true,
+            Err(e) => {
+                status = format!("Error: {:#}", e);
+                false
+            }
         };
 
-        detour.inject();
         Self {
+            available,
             value: 1.0,
             overwrite: false,
-            detour
+            transition_time: 0.0,
+            transition: None,
+            presets,
+            current_preset: 0,
+            new_preset_name: String::new(),
+            status,
+        }
+    }
+
+    pub fn settings(&self) -> ExposureSettings {
+        ExposureSettings {
+            value: self.target(),
+            overwrite: self.overwrite,
+        }
+    }
+
+    pub fn apply_settings(&mut self, settings: ExposureSettings) {
+        self.overwrite = settings.overwrite;
+        self.set_target(settings.value);
+    }
+
+    /// Where the exposure is going, which is the current value if it's not fading.
+    fn target(&self) -> f32 {
+        match &self.transition {
+            Some(transition) => from_ev(transition.to_ev),
+            None => self.value,
+        }
+    }
+
+    /// Goes to the given exposure, fading if there's a transition time.
+    pub fn set_target(&mut self, value: f32) {
+        let value = value.clamp(MIN_EXPOSURE, MAX_EXPOSURE);
+        if self.transition_time <= 0.0 {
+            self.value = value;
+            self.transition = None;
+            return;
+        }
+
+        self.transition = Some(Transition {
+            from_ev: ev(self.value),
+            to_ev: ev(value),
+            elapsed: 0.0,
+        });
+    }
+
+    /// Moves the exposure by the given amount of stops. Only does something while we're
+    /// overwriting the exposure.
+    pub fn step(&mut self, stops: f32) {
+        if self.overwrite {
+            self.set_target(from_ev(ev(self.target()) + stops));
         }
     }
 
+    /// Handles the exposure keys and advances the transition. Has to be called every frame,
+    /// even with the menu closed, so fades keep going while recording.
+    pub fn update(&mut self, ui: &imgui::Ui, hooks: &mut HookManager) {
+        if !ui.io().want_capture_keyboard {
+            if ui.is_key_pressed(EXPOSURE_UP_KEY) {
+                self.step(EV_STEP);
+            }
+            if ui.is_key_pressed(EXPOSURE_DOWN_KEY) {
+                self.step(-EV_STEP);
+            }
+        }
+
+        if let Some(transition) = &mut self.transition {
+            transition.elapsed += ui.io().delta_time;
+            let t = (transition.elapsed / self.transition_time.max(f32::EPSILON)).min(1.0);
+            self.value = from_ev(transition.from_ev + (transition.to_ev - transition.from_ev) * t);
+            if t >= 1.0 {
+                self.transition = None;
+            }
+        }
+
+        if let Some(params) = hooks.params(EXPOSURE_HOOK) {
+            params.active = self.overwrite as u32;
+            params.values[0] = self.value;
+        }
+    }
+
+    fn parse_presets(text: &str) -> Result<Vec<ExposurePreset>> {
+        let mut presets = Vec::new();
+        for block in parse_blocks(text)? {
+            if block.name != EXPOSURE_PRESET_HEADER {
+                continue;
+            }
+
+            let mut preset = ExposurePreset {
+                name: String::new(),
+                value: 1.0,
+            };
+            for (key, value) in block.fields {
+                match key {
+                    "name" => preset.name = value.to_string(),
+                    "value" => {
+                        preset.value = parse::<f32>(value)?.clamp(MIN_EXPOSURE, MAX_EXPOSURE)
+                    }
+                    _ => (),
+                }
+            }
+            presets.push(preset);
+        }
+
+        Ok(presets)
+    }
+
+    fn save_presets(&self) -> Result<()> {
+        let mut out = String::new();
+        for preset in self.presets.iter() {
+            write_header(&mut out, EXPOSURE_PRESET_HEADER);
+            let _ = writeln!(out, "name = {}", preset.name);
+            let _ = writeln!(out, "value = {}", preset.value);
+            out.push('\n');
+        }
+
+        save_data(EXPOSURE_PRESETS_FILE, &out)
+    }
+
     pub fn handle_ui(&mut self, ui: &imgui::Ui) {
-        ui.slider_config("Exposure", 1e-6, 3.0).flags(imgui::SliderFlags::LOGARITHMIC).build(&mut self.value);
+        if !self.available {
+            ui.text_disabled("Exposure override unavailable");
+            ui.text_wrapped(&self.status);
+            ui.separator();
+            return;
+        }
+
+        let mut value = self.value;
+        if ui.slider_config("Exposure", MIN_EXPOSURE, MAX_EXPOSURE).flags(imgui::SliderFlags::LOGARITHMIC).build(&mut value) {
+            // Dragging the slider should follow the mouse, not fade.
+            self.value = value;
+            self.transition = None;
+        }
+        ui.same_line();
+        ui.text(format!("{:+.2} EV", ev(self.value)));
         ui.checkbox("Overwrite", &mut self.overwrite);
-        ui.separator();
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Page Up/Page Down change the exposure by a third of a stop");
+        }
+
+        ui.slider_config("Transition (s)", 0.0, 30.0)
+            .build(&mut self.transition_time);
 
-        unsafe {
-            overwrite_tonemapping_enable = self.overwrite as u8;
-            overwrite_tonemapping_val = self.value;
+        if !self.presets.is_empty() {
+            self.current_preset = self.current_preset.min(self.presets.len() - 1);
+            let presets = &self.presets;
+            let indices: Vec<usize> = (0..presets.len()).collect();
+            ui.set_next_item_width(150.0);
+            ui.combo("##exposure_preset", &mut self.current_preset, &indices, |&i| {
+                format!("{} ({:+.2} EV)", presets[i].name, ev(presets[i].value)).into()
+            });
+            ui.same_line();
+            if ui.button("Go to preset") {
+                self.overwrite = true;
+                self.set_target(self.presets[self.current_preset].value);
+            }
+            ui.same_line();
+            if ui.button("Delete##exposure_preset") {
+                self.presets.remove(self.current_preset);
+                self.status = match self.save_presets() {
+                    Ok(()) => String::new(),
+                    Err(e) => format!("Error: {:#}", e),
+                };
+            }
         }
 
+        ui.set_next_item_width(150.0);
+        ui.input_text("##exposure_preset_name", &mut self.new_preset_name)
+            .hint("Preset name")
+            .build();
+        ui.same_line();
+        if ui.button("Save exposure preset") {
+            let name = if self.new_preset_name.is_empty() {
+                format!("{:+.2} EV", ev(self.value))
+            } else {
+                std::mem::take(&mut self.new_preset_name)
+            };
+            match self.presets.iter_mut().find(|p| p.name == name) {
+                Some(preset) => preset.value = self.value,
+                None => self.presets.push(ExposurePreset {
+                    name,
+                    value: self.value,
+                }),
+            }
+            self.status = match self.save_presets() {
+                Ok(()) => format!("Saved to {}", EXPOSURE_PRESETS_FILE),
+                Err(e) => format!("Error: {:#}", e),
+            };
+        }
+
+        if !self.status.is_empty() {
+            ui.text_wrapped(&self.status);
+        }
+        ui.separator();
     }
 }
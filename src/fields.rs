@@ -0,0 +1,429 @@
+//! Table of every light setting we know about. The editor widgets, the text format used by the
+//! clipboard, presets and scenes, and the clamping of values all come from here, so supporting a
+//! newly found field is a matter of adding it to the struct it's in, to `FieldId` and to
+//! `LIGHT_FIELDS`.
+use std::mem::offset_of;
+
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::VK_SHIFT;
+
+use crate::definitions::*;
+
+/// How the field is laid out in the game's memory. Values are always handled as `f32` on our
+/// side and converted when reading or writing the game's memory.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FieldType {
+    F32,
+    U32,
+    U8,
+}
+
+#[derive(Copy, Clone)]
+pub enum FieldWidget {
+    Slider,
+    /// A drag with the given speed, which gets 20 times faster while holding shift.
+    Drag(f32),
+    Checkbox,
+    /// One option per value, starting at 0.
+    Combo(&'static [&'static str]),
+    /// One checkbox per face of the point light's shadow cube.
+    ShadowFaces,
+}
+
+/// Returns where the `LightContainer` keeps its own copy of a field.
+pub type OwnedField = fn(&mut LightContainer) -> &mut f32;
+
+/// Every entry of `LIGHT_FIELDS`, in the same order, so the code can refer to a field without
+/// looking it up by name.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FieldId {
+    Brightness,
+    Radius,
+    ShadowBlendFactor,
+    Attenuation,
+    ShadowCastingMode,
+    ShadowFadeDistance,
+    ShadowFadeRange,
+    InnerAngle,
+    OuterAngle,
+    Softness,
+    CacheStaticShadows,
+    DynamicShadowFaceMask,
+}
+
+pub struct LightField {
+    pub id: FieldId,
+    /// Key used in the text format.
+    pub name: &'static str,
+    pub label: &'static str,
+    /// Offset from the start of the light (i.e. from the `LightEntity`), taken from the structs in
+    /// `definitions.rs` so both can't get out of sync.
+    pub offset: usize,
+    pub ty: FieldType,
+    /// Fields the `LightContainer` keeps its own value of, since what's in the game's memory is
+    /// that value after the master controls. Those are read and written through the container.
+    pub owned: Option<OwnedField>,
+    pub widget: FieldWidget,
+    pub min: f32,
+    pub max: f32,
+    /// What new lights get, the same values the game's spawn used to write. `None` leaves the
+    /// value the game gives to new lights.
+    pub default: Option<f32>,
+    pub unit: &'static str,
+    pub tooltip: &'static str,
+    pub kinds: &'static [LightKind],
+}
+
+fn owned_brightness(light: &mut LightContainer) -> &mut f32 {
+    &mut light.brightness
+}
+
+fn owned_radius(light: &mut LightContainer) -> &mut f32 {
+    &mut light.radius
+}
+
+fn owned_shadow_fade_distance(light: &mut LightContainer) -> &mut f32 {
+    &mut light.shadow_fade_distance
+}
+
+const SPOT: &[LightKind] = &[LightKind::SpotLight];
+const POINT: &[LightKind] = &[LightKind::PointLight];
+
+pub const LIGHT_FIELDS: &[LightField] = &[
+    LightField {
+        id: FieldId::Brightness,
+        name: "brightness",
+        label: "Brightness",
+        offset: offset_of!(LightEntity, light_settings.brightness),
+        ty: FieldType::F32,
+        owned: Some(owned_brightness),
+        widget: FieldWidget::Drag(1.0),
+        min: 0.1,
+        max: 100000.0,
+        default: Some(1000.0),
+        unit: "",
+        tooltip: "",
+        kinds: &LightKind::ALL,
+    },
+    LightField {
+        id: FieldId::Radius,
+        name: "radius",
+        label: "Radius",
+        offset: offset_of!(LightEntity, light_settings.radius),
+        ty: FieldType::F32,
+        owned: Some(owned_radius),
+        widget: FieldWidget::Slider,
+        min: 0.1,
+        max: 180.0,
+        default: Some(5.0),
+        unit: "m",
+        tooltip: "",
+        kinds: &LightKind::ALL,
+    },
+    LightField {
+        id: FieldId::ShadowBlendFactor,
+        name: "shadow_blend_factor",
+        label: "Shadow blend",
+        offset: offset_of!(LightEntity, shadow_blend_factor),
+        ty: FieldType::F32,
+        owned: None,
+        widget: FieldWidget::Slider,
+        min: 0.0001,
+        max: 1.0,
+        default: None,
+        unit: "",
+        tooltip: "",
+        kinds: &LightKind::ALL,
+    },
+    LightField {
+        id: FieldId::Attenuation,
+        name: "attenuation",
+        label: "Attenuation",
+        offset: offset_of!(LightEntity, light_settings.attenuation),
+        ty: FieldType::F32,
+        owned: None,
+        widget: FieldWidget::Slider,
+        min: 0.0001,
+        max: 1.0,
+        default: None,
+        unit: "",
+        tooltip: "",
+        kinds: &LightKind::ALL,
+    },
+    LightField {
+        id: FieldId::ShadowCastingMode,
+        name: "shadow_casting_mode",
+        label: "Shadow cast",
+        offset: offset_of!(LightEntity, shadow_casting_mode),
+        ty: FieldType::U32,
+        owned: None,
+        widget: FieldWidget::Combo(&SHADOWS_OPTIONS),
+        min: 0.0,
+        max: 2.0,
+        default: Some(1.0),
+        unit: "",
+        tooltip: "",
+        kinds: &LightKind::ALL,
+    },
+    LightField {
+        id: FieldId::ShadowFadeDistance,
+        name: "shadow_fade_distance",
+        label: "Shadow fade distance",
+        offset: offset_of!(LightEntity, shadow_fade_distance),
+        ty: FieldType::F32,
+        owned: Some(owned_shadow_fade_distance),
+        widget: FieldWidget::Slider,
+        min: 0.0,
+        max: 500.0,
+        default: None,
+        unit: "m",
+        tooltip: "Distance from the camera where the shadows start fading out",
+        kinds: &LightKind::ALL,
+    },
+    LightField {
+        id: FieldId::ShadowFadeRange,
+        name: "shadow_fade_range",
+        label: "Shadow fade range",
+        offset: offset_of!(LightEntity, shadow_fade_range),
+        ty: FieldType::F32,
+        owned: None,
+        widget: FieldWidget::Slider,
+        min: 0.0,
+        max: 100.0,
+        default: None,
+        unit: "m",
+        tooltip: "How long the shadows take to fade out completely",
+        kinds: &LightKind::ALL,
+    },
+    LightField {
+        id: FieldId::InnerAngle,
+        name: "inner_angle",
+        label: "Inner angle",
+        offset: offset_of!(SpotLight, inner_angle),
+        ty: FieldType::F32,
+        owned: None,
+        widget: FieldWidget::Slider,
+        min: 0.1,
+        max: 179.0,
+        default: Some(30.0),
+        unit: "deg",
+        tooltip: "",
+        kinds: SPOT,
+    },
+    LightField {
+        id: FieldId::OuterAngle,
+        name: "outer_angle",
+        label: "Outer angle",
+        offset: offset_of!(SpotLight, outer_angle),
+        ty: FieldType::F32,
+        owned: None,
+        widget: FieldWidget::Slider,
+        min: 1.1,
+        max: 180.0,
+        default: Some(45.0),
+        unit: "deg",
+        tooltip: "",
+        kinds: SPOT,
+    },
+    LightField {
+        id: FieldId::Softness,
+        name: "softness",
+        label: "Softness",
+        offset: offset_of!(SpotLight, softness),
+        ty: FieldType::F32,
+        owned: None,
+        widget: FieldWidget::Slider,
+        min: 0.1,
+        max: 100.0,
+        default: Some(2.0),
+        unit: "",
+        tooltip: "",
+        kinds: SPOT,
+    },
+    LightField {
+        id: FieldId::CacheStaticShadows,
+        name: "cache_static_shadows",
+        label: "Cache static shadows",
+        offset: offset_of!(PointLight, cache_static_shadows),
+        ty: FieldType::U8,
+        owned: None,
+        widget: FieldWidget::Checkbox,
+        min: 0.0,
+        max: 1.0,
+        default: Some(1.0),
+        unit: "",
+        tooltip: "",
+        kinds: POINT,
+    },
+    LightField {
+        id: FieldId::DynamicShadowFaceMask,
+        name: "dynamic_shadow_face_mask",
+        label: "Dynamic shadow faces",
+        offset: offset_of!(PointLight, dynamic_shadow_face_mask),
+        ty: FieldType::U8,
+        owned: None,
+        widget: FieldWidget::ShadowFaces,
+        min: 0.0,
+        max: 63.0,
+        default: Some(1.0),
+        unit: "",
+        tooltip: "",
+        kinds: POINT,
+    },
+];
+
+// `field` relies on every entry being at the index of its id.
+const _: () = {
+    let mut i = 0;
+    while i < LIGHT_FIELDS.len() {
+        assert!(LIGHT_FIELDS[i].id as usize == i, "LIGHT_FIELDS is out of order");
+        i += 1;
+    }
+};
+
+/// Index of the field with the given name, for the keys of the text format.
+pub fn field_index(name: &str) -> Option<usize> {
+    LIGHT_FIELDS.iter().position(|f| f.name == name)
+}
+
+pub fn field(id: FieldId) -> &'static LightField {
+    &LIGHT_FIELDS[id as usize]
+}
+
+impl LightField {
+    pub fn applies_to(&self, kind: LightKind) -> bool {
+        self.kinds.contains(&kind)
+    }
+
+    /// Whether every kind of light has this field, as opposed to the type specific ones.
+    pub fn is_shared(&self) -> bool {
+        self.kinds.len() == LightKind::ALL.len()
+    }
+
+    pub fn clamp(&self, value: f32) -> f32 {
+        let value = value.clamp(self.min, self.max);
+        match self.ty {
+            FieldType::F32 => value,
+            FieldType::U32 | FieldType::U8 => value.round(),
+        }
+    }
+
+    /// The value as it goes in the text format, integers without decimals.
+    pub fn format(&self, value: f32) -> String {
+        match self.ty {
+            FieldType::F32 => value.to_string(),
+            FieldType::U32 | FieldType::U8 => (value as u32).to_string(),
+        }
+    }
+
+    /// Reads the field straight from the game's memory.
+    ///
+    /// # Safety
+    /// `base` must point to the start of a light that has this field.
+    pub unsafe fn read_memory(&self, base: *const u8) -> f32 {
+        let ptr = base.add(self.offset);
+        match self.ty {
+            FieldType::F32 => std::ptr::read_unaligned(ptr as *const f32),
+            FieldType::U32 => std::ptr::read_unaligned(ptr as *const u32) as f32,
+            FieldType::U8 => *ptr as f32,
+        }
+    }
+
+    /// Writes the field straight to the game's memory, clamped to its range.
+    ///
+    /// # Safety
+    /// `base` must point to the start of a light that has this field.
+    pub unsafe fn write_memory(&self, base: *mut u8, value: f32) {
+        let value = self.clamp(value);
+        let ptr = base.add(self.offset);
+        match self.ty {
+            FieldType::F32 => std::ptr::write_unaligned(ptr as *mut f32, value),
+            FieldType::U32 => std::ptr::write_unaligned(ptr as *mut u32, value as u32),
+            FieldType::U8 => *ptr = value as u8,
+        }
+    }
+
+    /// The light's own value of this field, i.e. without the master controls applied.
+    pub fn read(&self, light: &mut LightContainer) -> f32 {
+        match self.owned {
+            Some(owned) => *owned(light),
+            None => unsafe { self.read_memory(light.light.as_mut_ptr()) },
+        }
+    }
+
+    pub fn write(&self, light: &mut LightContainer, value: f32) {
+        match self.owned {
+            Some(owned) => *owned(light) = self.clamp(value),
+            None => unsafe { self.write_memory(light.light.as_mut_ptr(), value) },
+        }
+    }
+
+    /// Draws the field's widget. Returns true when the value changed.
+    pub fn render(&self, ui: &imgui::Ui, value: &mut f32, camera: Option<RotationMatrix>) -> bool {
+        let label = if self.unit.is_empty() {
+            self.label.to_string()
+        } else {
+            format!("{} ({})", self.label, self.unit)
+        };
+
+        let changed = match self.widget {
+            FieldWidget::Slider => ui
+                .slider_config(&label, f32::MIN, f32::MAX)
+                .range(self.min, self.max)
+                .build(value),
+            FieldWidget::Drag(speed) => imgui::Drag::new(&label)
+                .range(self.min, self.max)
+                .speed(if ui.is_key_index_down(VK_SHIFT as _) {
+                    speed * 20.0
+                } else {
+                    speed
+                })
+                .build(ui, value),
+            FieldWidget::Checkbox => {
+                let mut checked = *value != 0.0;
+                let changed = ui.checkbox(&label, &mut checked);
+                *value = checked as u8 as f32;
+                changed
+            }
+            FieldWidget::Combo(options) => {
+                let mut selected = (*value as usize).min(options.len() - 1);
+                let indices: Vec<usize> = (0..options.len()).collect();
+                let changed = ui.combo(&label, &mut selected, &indices, |&i| options[i].into());
+                *value = selected as f32;
+                changed
+            }
+            FieldWidget::ShadowFaces => {
+                let mut mask = *value as u8;
+                let changed = shadow_face_mask_ui(ui, &mut mask, camera);
+                *value = mask as f32;
+                changed
+            }
+        };
+
+        if !self.tooltip.is_empty() && ui.is_item_hovered() {
+            ui.tooltip_text(self.tooltip);
+        }
+
+        changed
+    }
+}
+
+/// Draws the widgets of every field the light has, either the shared ones or the type specific
+/// ones.
+pub fn fields_ui(
+    ui: &imgui::Ui,
+    light: &mut LightContainer,
+    shared: bool,
+    camera: Option<RotationMatrix>,
+) {
+    let kind = light.light.kind();
+    let fields = LIGHT_FIELDS
+        .iter()
+        .filter(|f| f.applies_to(kind) && f.is_shared() == shared);
+
+    for field in fields {
+        let mut value = field.read(light);
+        if field.render(ui, &mut value, camera) {
+            field.write(light, value);
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use std::f32::consts::{PI, TAU};
+
+use imgui::MouseButton;
+
+use crate::definitions::*;
+use crate::fields::{field, FieldId};
+use crate::mixer::MasterControls;
+use crate::overlay::Projection;
+use crate::snapping::Snapping;
+
+// How close, in pixels, the mouse needs to be to grab a handle.
+const GRAB_DISTANCE: f32 = 6.0;
+const RING_SEGMENTS: usize = 48;
+const AXIS_COLORS: [[f32; 4]; 3] = [
+    [0.9, 0.2, 0.2, 1.0],
+    [0.2, 0.9, 0.2, 1.0],
+    [0.3, 0.4, 1.0, 1.0],
+];
+const ACTIVE_COLOR: [f32; 4] = [1.0, 0.85, 0.0, 1.0];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GizmoSpace {
+    World,
+    Camera,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Handle {
+    Translate(usize),
+    Rotate(usize),
+    Radius,
+}
+
+/// In-viewport handles to move the selected light along an axis, rotate spotlights and change
+/// the radius by dragging its sphere outline.
+pub struct Gizmo {
+    pub enabled: bool,
+    pub space: GizmoSpace,
+    /// Length of the axis arrows, in pixels.
+    pub size: f32,
+    active: Option<Handle>,
+    // Where the light was when the drag started and how much it has been dragged since, so
+    // snapping doesn't eat the small per-frame movements.
+    drag_start: Option<(Position, RotationMatrix)>,
+    drag_amount: f32,
+}
+
+impl Gizmo {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            space: GizmoSpace::World,
+            size: 90.0,
+            active: None,
+            drag_start: None,
+            drag_amount: 0.0,
+        }
+    }
+
+    pub fn handle_ui(&mut self, ui: &imgui::Ui) {
+        ui.checkbox("Gizmo", &mut self.enabled);
+        if !self.enabled {
+            return;
+        }
+
+        ui.same_line();
+        ui.radio_button("World", &mut self.space, GizmoSpace::World);
+        ui.same_line();
+        ui.radio_button("Camera", &mut self.space, GizmoSpace::Camera);
+    }
+
+    fn axes(&self, projection: &Projection) -> [Position; 3] {
+        match self.space {
+            GizmoSpace::World => [
+                Position::new(1.0, 0.0, 0.0),
+                Position::new(0.0, 1.0, 0.0),
+                Position::new(0.0, 0.0, 1.0),
+            ],
+            GizmoSpace::Camera => [
+                projection.rot.right(),
+                projection.rot.forward(),
+                projection.rot.up(),
+            ],
+        }
+    }
+
+    /// Draws the gizmo for `light` and applies any drag to it. Returns true if the mouse is
+    /// hovering or dragging a handle, so clicks don't go to anything else. The radius sphere is
+    /// the one the game uses, i.e. with the master radius scale applied.
+    pub fn update(
+        &mut self,
+        ui: &imgui::Ui,
+        projection: &Projection,
+        snapping: &Snapping,
+        master: &MasterControls,
+        light: &mut LightContainer,
+    ) -> bool {
+        if !self.enabled || light.attach_camera {
+            self.active = None;
+            return false;
+        }
+
+        let (pos, rot) = light.get_pos_rot();
+        let (Some(center), Some(ppm)) = (projection.project(pos), projection.pixels_per_meter(pos))
+        else {
+            self.active = None;
+            return false;
+        };
+
+        let io = ui.io();
+        let mouse = io.mouse_pos;
+        let axes = self.axes(projection);
+        let axis_length = self.size / ppm;
+        let is_spotlight = matches!(light.light, LightType::SpotLight(_));
+
+        let arrows: Vec<Option<[f32; 2]>> = axes
+            .iter()
+            .map(|axis| projection.project(pos + *axis * axis_length))
+            .collect();
+        let rings: Vec<Vec<[f32; 2]>> = if is_spotlight {
+            axes.iter()
+                .map(|axis| ring(projection, pos, *axis, axis_length * 0.7))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let radius_px = master.apply_radius(light.radius) * ppm;
+
+        // Find what's under the mouse, if we're not dragging already.
+        let hovered = self.active.or_else(|| {
+            if io.want_capture_mouse {
+                return None;
+            }
+
+            let arrow = arrows.iter().enumerate().find_map(|(i, end)| {
+                let end = (*end)?;
+                (distance_to_segment(mouse, center, end) < GRAB_DISTANCE)
+                    .then_some(Handle::Translate(i))
+            });
+            let ring = rings.iter().enumerate().find_map(|(i, points)| {
+                points
+                    .windows(2)
+                    .any(|w| distance_to_segment(mouse, w[0], w[1]) < GRAB_DISTANCE)
+                    .then_some(Handle::Rotate(i))
+            });
+            let sphere =
+                ((distance(mouse, center) - radius_px).abs() < GRAB_DISTANCE).then_some(Handle::Radius);
+
+            arrow.or(ring).or(sphere)
+        });
+
+        if self.active.is_none() && hovered.is_some() && ui.is_mouse_clicked(MouseButton::Left) {
+            self.active = hovered;
+            self.drag_start = Some((pos, rot));
+            self.drag_amount = 0.0;
+        }
+        if !ui.is_mouse_down(MouseButton::Left) {
+            self.active = None;
+        }
+
+        let delta = io.mouse_delta;
+        let (start_pos, start_rot) = self.drag_start.unwrap_or((pos, rot));
+        match self.active {
+            Some(Handle::Translate(i)) => {
+                if let Some(end) = arrows[i] {
+                    let screen_axis = [end[0] - center[0], end[1] - center[1]];
+                    let screen_len = (screen_axis[0].powi(2) + screen_axis[1].powi(2)).sqrt();
+                    if screen_len > 1.0 {
+                        let pixels =
+                            (delta[0] * screen_axis[0] + delta[1] * screen_axis[1]) / screen_len;
+                        self.drag_amount += pixels * axis_length / screen_len;
+                        let new_pos = start_pos + axes[i] * self.drag_amount;
+                        light.set_pos_rot(snapping.snap_position(new_pos), rot);
+                    }
+                }
+            }
+            Some(Handle::Rotate(i)) => {
+                let previous = [mouse[0] - delta[0], mouse[1] - delta[1]];
+                let mut angle = screen_angle(center, mouse) - screen_angle(center, previous);
+                // The screen angle jumps from PI to -PI, keep the step in that range.
+                angle = (angle + PI).rem_euclid(TAU) - PI;
+                // Screen angles grow clockwise, so when the axis points to the camera it's the
+                // other way around.
+                if axes[i].dot(projection.rot.forward()) < 0.0 {
+                    angle = -angle;
+                }
+                self.drag_amount += angle;
+                let new_rot = start_rot.rotated(axes[i], self.drag_amount);
+                light.set_pos_rot(pos, snapping.snap_rotation(new_rot));
+            }
+            Some(Handle::Radius) => {
+                let radius = master.remove_radius(distance(mouse, center) / ppm);
+                field(FieldId::Radius).write(light, radius);
+            }
+            None => (),
+        }
+
+        let draw_list = ui.get_background_draw_list();
+        let color_for = |handle: Handle, base: [f32; 4]| {
+            if hovered == Some(handle) {
+                ACTIVE_COLOR
+            } else {
+                base
+            }
+        };
+
+        draw_list
+            .add_circle(center, radius_px, color_for(Handle::Radius, [1.0, 1.0, 1.0, 0.5]))
+            .num_segments(RING_SEGMENTS as u32)
+            .build();
+
+        for (i, points) in rings.into_iter().enumerate() {
+            let color = color_for(Handle::Rotate(i), AXIS_COLORS[i]);
+            draw_list.add_polyline(points, color).thickness(2.0).build();
+        }
+
+        for (i, end) in arrows.iter().enumerate() {
+            if let Some(end) = end {
+                let color = color_for(Handle::Translate(i), AXIS_COLORS[i]);
+                draw_list.add_line(center, *end, color).thickness(3.0).build();
+                draw_list.add_circle(*end, 4.0, color).filled(true).build();
+            }
+        }
+
+        hovered.is_some()
+    }
+}
+
+/// Projected circle around `center`, perpendicular to `axis`. Empty if part of it is behind the
+/// camera.
+fn ring(projection: &Projection, center: Position, axis: Position, radius: f32) -> Vec<[f32; 2]> {
+    let helper = if axis.z.abs() < 0.9 {
+        Position::new(0.0, 0.0, 1.0)
+    } else {
+        Position::new(1.0, 0.0, 0.0)
+    };
+    let u = axis.cross(helper).normalized();
+    let v = axis.cross(u);
+
+    let points: Option<Vec<[f32; 2]>> = (0..=RING_SEGMENTS)
+        .map(|i| {
+            let t = (i as f32) / (RING_SEGMENTS as f32) * TAU;
+            projection.project(center + u * (radius * t.cos()) + v * (radius * t.sin()))
+        })
+        .collect();
+    points.unwrap_or_default()
+}
+
+fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt()
+}
+
+fn distance_to_segment(p: [f32; 2], a: [f32; 2], b: [f32; 2]) -> f32 {
+    let ab = [b[0] - a[0], b[1] - a[1]];
+    let len2 = ab[0] * ab[0] + ab[1] * ab[1];
+    if len2 <= f32::EPSILON {
+        return distance(p, a);
+    }
+
+    let t = (((p[0] - a[0]) * ab[0] + (p[1] - a[1]) * ab[1]) / len2).clamp(0.0, 1.0);
+    distance(p, [a[0] + ab[0] * t, a[1] + ab[1] * t])
+}
+
+fn screen_angle(center: [f32; 2], p: [f32; 2]) -> f32 {
+    (p[1] - center[1]).atan2(p[0] - center[0])
+}
@@ -0,0 +1,75 @@
+use imgui::{DragDropFlags, TreeNodeFlags};
+
+use crate::definitions::*;
+
+/// A named set of lights, shown as a collapsible section in the main window list. Disabling a
+/// group turns off all of its lights without touching their own on/off state.
+pub struct LightGroup {
+    pub id: usize,
+    pub name: String,
+    pub enabled: bool,
+}
+
+impl LightGroup {
+    pub fn new(id: usize, name: String) -> Self {
+        Self {
+            id,
+            name,
+            enabled: true,
+        }
+    }
+}
+
+/// Whether the group with the given id is enabled. Lights without a group, or whose group
+/// doesn't exist anymore, are always enabled.
+pub fn is_group_enabled(groups: &[LightGroup], group: Option<usize>) -> bool {
+    !groups.iter().any(|g| Some(g.id) == group && !g.enabled)
+}
+
+/// Things that happened while drawing a group header that need the whole list to be handled.
+pub enum GroupEvent {
+    /// A light (by its id) got dropped on the group header.
+    LightDropped(usize),
+    SelectAll,
+    Ungroup,
+}
+
+impl LightGroup {
+    /// Draws the group header and its controls. Returns whether the group is expanded alongside
+    /// any event that happened.
+    pub fn render_header(&mut self, ui: &imgui::Ui) -> (bool, Option<GroupEvent>) {
+        let mut event = None;
+        let open = ui.collapsing_header(
+            format!("{}###group", self.name),
+            TreeNodeFlags::DEFAULT_OPEN,
+        );
+
+        if let Some(target) = ui.drag_drop_target() {
+            if let Some(Ok(payload)) =
+                target.accept_payload::<usize, _>(LIGHT_PAYLOAD, DragDropFlags::empty())
+            {
+                event = Some(GroupEvent::LightDropped(payload.data));
+            }
+            target.pop();
+        }
+
+        if !open {
+            return (false, event);
+        }
+
+        ui.checkbox("Group enabled", &mut self.enabled);
+        ui.same_line();
+        ui.set_next_item_width(120.0);
+        ui.input_text("Name", &mut self.name).build();
+        ui.same_line();
+        if ui.small_button("Select all") {
+            event = Some(GroupEvent::SelectAll);
+        }
+        ui.same_line();
+        if ui.small_button("Ungroup") {
+            event = Some(GroupEvent::Ungroup);
+        }
+
+        (true, event)
+    }
+}
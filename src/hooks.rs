@@ -0,0 +1,182 @@
+//! Detours used by the render overrides (exposure and such). Every hook is an asm stub in
+//! `injection.asm` that jumps back to the game's code through a `HookParams` block, which is also
+//! where it reads its settings from. Adding a new override means writing the stub, declaring its
+//! block as a `#[no_mangle]` static and registering both with the `HookManager`.
+use anyhow::{bail, Context, Result};
+use memory_rs::internal::injections::{Detour, Inject};
+use memory_rs::internal::memory::MemoryPattern;
+use memory_rs::internal::process_info::ProcessInfo;
+
+/// How many values a hook can get from us.
+pub const HOOK_VALUES: usize = 15;
+
+/// Block shared between us and an asm stub. The layout is fixed since the stubs read it by
+/// offset:
+/// * `0x00` - where the stub jumps back to, written by the `Detour`.
+/// * `0x08` - whether the stub should overwrite the game's values or let them pass through.
+/// * `0x0C` - the values, 4 bytes each.
+#[repr(C)]
+pub struct HookParams {
+    pub jump_back: usize,
+    pub active: u32,
+    pub values: [f32; HOOK_VALUES],
+}
+
+impl HookParams {
+    pub const fn new() -> Self {
+        Self {
+            jump_back: 0,
+            active: 0,
+            values: [0.0; HOOK_VALUES],
+        }
+    }
+}
+
+pub struct HookDefinition {
+    pub name: &'static str,
+    pub pattern: MemoryPattern,
+    /// Where the detour goes, from the start of the pattern.
+    pub offset: usize,
+    /// Bytes the detour overwrites, they have to cover whole instructions and the stub has to
+    /// replicate them.
+    pub size: usize,
+    /// Address of the asm stub.
+    pub stub: usize,
+    pub params: *mut HookParams,
+}
+
+struct Hook {
+    name: &'static str,
+    /// `None` when we couldn't find the code to hook, which happens with other game versions.
+    detour: Option<Detour>,
+    params: *mut HookParams,
+    enabled: bool,
+}
+
+/// Keeps track of every hook we put in the game's code, so they can be turned on and off, and
+/// all of them get removed (restoring the original bytes) when we get ejected.
+pub struct HookManager {
+    hooks: Vec<Hook>,
+    status: String,
+}
+
+impl HookManager {
+    pub fn new() -> Self {
+        Self {
+            hooks: Vec::new(),
+            status: String::new(),
+        }
+    }
+
+    /// Looks for the hook's code and injects it. If the code can't be found the hook still gets
+    /// registered, as unavailable, so the override can tell the user instead of crashing.
+    pub fn register(&mut self, proc_info: &ProcessInfo, definition: HookDefinition) -> Result<()> {
+        let HookDefinition {
+            name,
+            pattern,
+            offset,
+            size,
+            stub,
+            params,
+        } = definition;
+
+        let addr = proc_info
+            .region
+            .scan_aob(&pattern)
+            .ok()
+            .flatten()
+            .map(|addr| addr + offset);
+
+        let detour = addr.map(|addr| unsafe {
+            Detour::new(addr, size, stub, Some(&mut *(&raw mut (*params).jump_back)))
+        });
+
+        let found = detour.is_some();
+        self.hooks.push(Hook {
+            name,
+            detour,
+            params,
+            enabled: false,
+        });
+
+        if !found {
+            bail!("Couldn't find the code for the {} hook", name);
+        }
+
+        self.set_enabled(name, true)
+    }
+
+    fn hook_mut(&mut self, name: &str) -> Option<&mut Hook> {
+        self.hooks.iter_mut().find(|h| h.name == name)
+    }
+
+    /// Injects or removes the hook. Removing it puts the game's original bytes back.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> Result<()> {
+        let hook = self
+            .hook_mut(name)
+            .with_context(|| format!("There's no {} hook", name))?;
+        let Some(detour) = &mut hook.detour else {
+            bail!("The {} hook is unavailable", name);
+        };
+
+        if hook.enabled != enabled {
+            if enabled {
+                detour.inject();
+            } else {
+                detour.remove_injection();
+            }
+            hook.enabled = enabled;
+        }
+
+        Ok(())
+    }
+
+    /// The block the hook's stub reads from, if the hook is available.
+    pub fn params(&mut self, name: &str) -> Option<&mut HookParams> {
+        self.hook_mut(name)
+            .filter(|h| h.detour.is_some())
+            .map(|h| unsafe { &mut *h.params })
+    }
+
+    /// Removes every hook, leaving the game's code as we found it.
+    pub fn remove_all(&mut self) {
+        for hook in self.hooks.iter_mut().filter(|h| h.enabled) {
+            if let Some(detour) = &mut hook.detour {
+                detour.remove_injection();
+            }
+            hook.enabled = false;
+        }
+    }
+
+    pub fn handle_ui(&mut self, ui: &imgui::Ui) {
+        let mut toggled = None;
+        for hook in self.hooks.iter() {
+            if hook.detour.is_none() {
+                ui.text_disabled(format!("{} (not found)", hook.name));
+                continue;
+            }
+
+            let mut enabled = hook.enabled;
+            if ui.checkbox(hook.name, &mut enabled) {
+                toggled = Some((hook.name, enabled));
+            }
+        }
+
+        if let Some((name, enabled)) = toggled {
+            self.status = match self.set_enabled(name, enabled) {
+                Ok(()) => String::new(),
+                Err(e) => format!("Error: {:#}", e),
+            };
+        }
+
+        if !self.status.is_empty() {
+            ui.text_wrapped(&self.status);
+        }
+    }
+}
+
+impl Drop for HookManager {
+    fn drop(&mut self) {
+        self.remove_all();
+    }
+}
@@ -1,8 +1,7 @@
 .data
-; Jumpback addr.
-EXTERN overwrite_tonemapping_jmb: qword
-EXTERN overwrite_tonemapping_val: dword
-EXTERN overwrite_tonemapping_enable: byte
+; Parameter blocks, see `HookParams` in hooks.rs for the layout:
+; +00h jump back address, +08h active, +0Ch values (4 bytes each).
+EXTERN tonemapping_params: qword
 
 .code
 overwrite_tonemapping PROC
@@ -20,7 +19,7 @@ overwrite_tonemapping PROC
     push rbx
     push rcx
 
-    mov bl, byte ptr [overwrite_tonemapping_enable]
+    mov bl, byte ptr [tonemapping_params + 08h]
     test bl, bl
     jz @f
 
@@ -28,7 +27,7 @@ overwrite_tonemapping PROC
     lea rbx, [rdx+1b20h]
     mov rcx, [rbx]
     add rcx, 14h
-    mov ebx, dword ptr [overwrite_tonemapping_val]
+    mov ebx, dword ptr [tonemapping_params + 0Ch]
     mov dword ptr [rcx], ebx
     ; --
 
@@ -46,7 +45,7 @@ overwrite_tonemapping PROC
     mov r15, [rsp + 188h]
 
 original:
-    jmp [overwrite_tonemapping_jmb]
+    jmp qword ptr [tonemapping_params]
 
 
 overwrite_tonemapping ENDP
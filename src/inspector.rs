@@ -0,0 +1,283 @@
+use std::mem::{offset_of, size_of};
+
+use anyhow::{anyhow, bail, Context, Result};
+use imgui::Condition;
+
+use crate::definitions::*;
+use crate::fields::*;
+use crate::snapshot::parse;
+
+/// How many frames a changed byte stays highlighted, a single frame would be too short to notice.
+const HIGHLIGHT_FRAMES: u32 = 60;
+const HIGHLIGHT_COLOR: [f32; 4] = [1.0, 0.4, 0.2, 1.0];
+const KNOWN_COLOR: [f32; 4] = [0.5, 0.8, 1.0, 1.0];
+
+/// Things we know about that aren't in `LIGHT_FIELDS`, since they're not settings.
+const KNOWN_OFFSETS: [(usize, &str); 5] = [
+    (offset_of!(LightEntity, entity.flags), "flags"),
+    (offset_of!(LightEntity, entity.rot_matrix), "rot_matrix"),
+    (offset_of!(LightEntity, entity.pos), "pos"),
+    (offset_of!(LightEntity, light_settings.color), "color"),
+    (offset_of!(LightEntity, is_enabled), "is_enabled"),
+];
+
+/// Where the structs we know about end, i.e. the end of `SpotLight` and `PointLight`.
+fn known_size(kind: LightKind) -> usize {
+    match kind {
+        LightKind::SpotLight => size_of::<SpotLight>(),
+        LightKind::PointLight => size_of::<PointLight>(),
+    }
+}
+
+/// Name of the field at the given offset, if we know about it.
+fn field_at(kind: LightKind, offset: usize) -> Option<&'static str> {
+    LIGHT_FIELDS
+        .iter()
+        .find(|f| f.applies_to(kind) && f.offset == offset)
+        .map(|f| f.name)
+        .or_else(|| {
+            KNOWN_OFFSETS
+                .iter()
+                .find(|(o, _)| *o == offset)
+                .map(|(_, name)| *name)
+        })
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InspectorView {
+    Hex,
+    Float,
+    Int,
+}
+
+/// Debug window that shows the raw memory of the last selected light, to find out what the
+/// fields we haven't mapped yet do. Bytes that change get highlighted for a moment, and any
+/// value can be written at an offset, after which the light gets re-rendered with `set_flags`.
+/// Keep in mind the fields the `LightContainer` owns (like the brightness) get written over by it
+/// every frame.
+pub struct MemoryInspector {
+    pub open: bool,
+    view: InspectorView,
+    /// Bytes to show past the end of the structs we know about.
+    extra: i32,
+    light_id: Option<usize>,
+    previous: Vec<u8>,
+    /// Frames since every byte last changed.
+    age: Vec<u32>,
+    write_offset: String,
+    write_type: FieldType,
+    write_value: String,
+    status: String,
+}
+
+impl MemoryInspector {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            view: InspectorView::Hex,
+            extra: 0,
+            light_id: None,
+            previous: Vec::new(),
+            age: Vec::new(),
+            write_offset: String::new(),
+            write_type: FieldType::F32,
+            write_value: String::new(),
+            status: String::new(),
+        }
+    }
+
+    /// Compares the light's memory with the last frame's.
+    fn update(&mut self, light: &mut LightContainer) -> Vec<u8> {
+        let size = known_size(light.light.kind()) + self.extra as usize;
+        let ptr = light.light.as_mut_ptr();
+        let bytes = unsafe { std::slice::from_raw_parts(ptr, size) }.to_vec();
+
+        if self.light_id != Some(light.id) || self.previous.len() != size {
+            self.light_id = Some(light.id);
+            self.age = vec![HIGHLIGHT_FRAMES; size];
+        } else {
+            for ((age, new), old) in self.age.iter_mut().zip(&bytes).zip(&self.previous) {
+                *age = if new != old { 0 } else { age.saturating_add(1) };
+            }
+        }
+
+        self.previous = bytes.clone();
+        bytes
+    }
+
+    pub fn render(&mut self, ui: &imgui::Ui, light: Option<&mut LightContainer>, world: usize) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        ui.window("Memory inspector")
+            .size([520.0, 480.0], Condition::FirstUseEver)
+            .opened(&mut open)
+            .build(|| {
+                let Some(light) = light else {
+                    ui.text("Select a light to inspect it");
+                    return;
+                };
+
+                let bytes = self.update(light);
+                let kind = light.light.kind();
+                ui.text(format!(
+                    "{} ({}) at {:p}",
+                    light.name,
+                    kind.name(),
+                    light.light.as_mut_ptr()
+                ));
+
+                ui.radio_button("Hex", &mut self.view, InspectorView::Hex);
+                ui.same_line();
+                ui.radio_button("Float", &mut self.view, InspectorView::Float);
+                ui.same_line();
+                ui.radio_button("Int", &mut self.view, InspectorView::Int);
+                ui.slider("Extra bytes", 0, 0x200, &mut self.extra);
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("Reads past the structs we know, careful with big values");
+                }
+
+                self.write_ui(ui, light, world);
+                ui.separator();
+
+                ui.child_window("##memory").build(|| match self.view {
+                    InspectorView::Hex => self.hex_view(ui, kind, &bytes),
+                    InspectorView::Float | InspectorView::Int => self.value_view(ui, kind, &bytes),
+                });
+            });
+        self.open = open;
+    }
+
+    fn hex_view(&self, ui: &imgui::Ui, kind: LightKind, bytes: &[u8]) {
+        for (row, chunk) in bytes.chunks(16).enumerate() {
+            ui.text(format!("{:04X}:", row * 16));
+            for (i, byte) in chunk.iter().enumerate() {
+                let offset = row * 16 + i;
+                ui.same_line();
+                let text = format!("{:02X}", byte);
+                if self.age[offset] < HIGHLIGHT_FRAMES {
+                    ui.text_colored(HIGHLIGHT_COLOR, text);
+                } else if field_at(kind, offset).is_some() {
+                    ui.text_colored(KNOWN_COLOR, text);
+                } else {
+                    ui.text(text);
+                }
+
+                if ui.is_item_hovered() {
+                    match field_at(kind, offset) {
+                        Some(name) => ui.tooltip_text(format!("0x{:X} - {}", offset, name)),
+                        None => ui.tooltip_text(format!("0x{:X}", offset)),
+                    }
+                }
+            }
+        }
+    }
+
+    /// One line per 4 bytes, read as a float or as an integer.
+    fn value_view(&self, ui: &imgui::Ui, kind: LightKind, bytes: &[u8]) {
+        for (row, chunk) in bytes.chunks_exact(4).enumerate() {
+            let offset = row * 4;
+            let raw = [chunk[0], chunk[1], chunk[2], chunk[3]];
+            let value = match self.view {
+                InspectorView::Float => format!("{}", f32::from_le_bytes(raw)),
+                _ => format!(
+                    "{} (0x{:08X})",
+                    i32::from_le_bytes(raw),
+                    u32::from_le_bytes(raw)
+                ),
+            };
+            let name = field_at(kind, offset).unwrap_or("");
+            let text = format!("{:04X}: {:<28} {}", offset, value, name);
+
+            if self.age[offset..offset + 4]
+                .iter()
+                .any(|age| *age < HIGHLIGHT_FRAMES)
+            {
+                ui.text_colored(HIGHLIGHT_COLOR, text);
+            } else if !name.is_empty() {
+                ui.text_colored(KNOWN_COLOR, text);
+            } else {
+                ui.text(text);
+            }
+        }
+    }
+
+    fn write_ui(&mut self, ui: &imgui::Ui, light: &mut LightContainer, world: usize) {
+        const TYPES: [FieldType; 3] = [FieldType::F32, FieldType::U32, FieldType::U8];
+
+        ui.set_next_item_width(80.0);
+        ui.input_text("Offset", &mut self.write_offset)
+            .hint("0x180")
+            .build();
+        ui.same_line();
+        ui.set_next_item_width(70.0);
+        let mut ty = TYPES
+            .iter()
+            .position(|t| *t == self.write_type)
+            .unwrap_or(0);
+        if ui.combo("Type", &mut ty, &TYPES, |t| format!("{:?}", t).into()) {
+            self.write_type = TYPES[ty];
+        }
+        ui.same_line();
+        ui.set_next_item_width(100.0);
+        ui.input_text("Value", &mut self.write_value).build();
+        ui.same_line();
+        if ui.button("Write") {
+            self.status = match self.write(light, world) {
+                Ok(()) => String::new(),
+                Err(e) => format!("Error: {:#}", e),
+            };
+        }
+
+        if !self.status.is_empty() {
+            ui.text_wrapped(&self.status);
+        }
+    }
+
+    fn write(&self, light: &mut LightContainer, world: usize) -> Result<()> {
+        let offset = parse_number(&self.write_offset)?;
+        let size = match self.write_type {
+            FieldType::F32 | FieldType::U32 => 4,
+            FieldType::U8 => 1,
+        };
+        let limit = known_size(light.light.kind()) + self.extra as usize;
+        if offset.checked_add(size).filter(|end| *end <= limit).is_none() {
+            bail!("0x{:X} is past what we're showing (0x{:X})", offset, limit);
+        }
+
+        let value = self.write_value.trim();
+        unsafe {
+            let ptr = light.light.as_mut_ptr().add(offset);
+            match self.write_type {
+                FieldType::F32 => {
+                    let value: f32 = parse(value)?;
+                    std::ptr::write_unaligned(ptr as *mut f32, value);
+                }
+                FieldType::U32 => {
+                    let value = u32::try_from(parse_number(value)?)
+                        .with_context(|| format!("`{}` doesn't fit in a u32", value))?;
+                    std::ptr::write_unaligned(ptr as *mut u32, value);
+                }
+                FieldType::U8 => {
+                    *ptr = u8::try_from(parse_number(value)?)
+                        .with_context(|| format!("`{}` doesn't fit in a u8", value))?;
+                }
+            }
+        }
+
+        light.update_render(world);
+        Ok(())
+    }
+}
+
+/// Parses a decimal number or a `0x` prefixed hexadecimal one.
+fn parse_number(text: &str) -> Result<usize> {
+    let text = text.trim();
+    let parsed = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => text.parse(),
+    };
+    parsed.map_err(|_| anyhow!("Couldn't parse `{}`", text))
+}
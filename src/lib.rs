@@ -4,25 +4,63 @@
 //! Offset of the CR4Player Memory Pool: [$process + 2d56848]
 use std::panic::PanicHookInfo;
 
-use anyhow::{Context, Result};
-use imgui::ColorEditFlags;
+use anyhow::{bail, Context, Result};
 use memory_rs::generate_aob_pattern;
 use memory_rs::internal::process_info::ProcessInfo;
 
 use hudhook::hooks::dx11::ImguiDx11Hooks;
 use hudhook::hooks::dx12::ImguiDx12Hooks;
 use hudhook::ImguiRenderLoop;
-use imgui::Condition;
+use imgui::{Condition, TreeNodeFlags};
 
 mod definitions;
 mod detect_api;
 mod pointer;
+mod hooks;
 mod exposure;
+mod mixer;
+mod snapshot;
+mod groups;
+mod batch;
+mod scene;
+mod math;
+mod placement;
+mod overlay;
+mod gizmo;
+mod nudge;
+mod snapping;
+mod array_tool;
+mod mirror;
+mod rigs;
+mod presets;
+mod defaults;
+mod fields;
+mod inspector;
+mod area;
 
 use definitions::*;
 use detect_api::*;
 use pointer::*;
+use hooks::*;
 use exposure::*;
+use mixer::*;
+use snapshot::*;
+use groups::*;
+use batch::*;
+use scene::*;
+use placement::*;
+use overlay::*;
+use gizmo::*;
+use nudge::*;
+use snapping::*;
+use array_tool::*;
+use mirror::*;
+use rigs::*;
+use presets::*;
+use defaults::*;
+use inspector::*;
+use area::*;
+use fields::{field, FieldId};
 use windows_sys::Win32::UI::WindowsAndMessaging::MessageBoxA;
 
 use hudhook::windows::Win32::Foundation::HINSTANCE;
@@ -37,7 +75,31 @@ struct LitcherContext {
     show: bool,
     player: CR4Player,
     id_track: usize,
+    hooks: HookManager,
     tonemapping: ToneMappingContainer,
+    master: MasterControls,
+    copied: Option<LightSnapshot>,
+    groups: Vec<LightGroup>,
+    group_id_track: usize,
+    new_group_name: String,
+    batch: BatchEdit,
+    name_filter: String,
+    scene_name: String,
+    scene_status: String,
+    placement: PlacementOptions,
+    last_selected: Option<usize>,
+    overlay: OverlaySettings,
+    gizmo: Gizmo,
+    move_mode: MoveMode,
+    snapping: Snapping,
+    array_tool: ArrayTool,
+    mirror: MirrorTool,
+    rigs: RigTool,
+    presets: PresetLibrary,
+    defaults: SpawnDefaults,
+    inspector: MemoryInspector,
+    areas: Vec<AreaLight>,
+    area_id_track: usize,
     cursor: WitcherCursor
 }
 
@@ -122,7 +184,8 @@ impl LitcherContext {
 
         let lights = Vec::new();
 
-        let tonemapping = ToneMappingContainer::new(&proc_info);
+        let mut hooks = HookManager::new();
+        let tonemapping = ToneMappingContainer::new(&proc_info, &mut hooks);
 
         let cursor = {
             let region = &proc_info.region;
@@ -143,7 +206,31 @@ impl LitcherContext {
             show: true,
             player: CR4Player::new(player),
             id_track: 0,
+            hooks,
             tonemapping,
+            master: MasterControls::new(),
+            copied: None,
+            groups: Vec::new(),
+            group_id_track: 0,
+            new_group_name: String::new(),
+            batch: BatchEdit::new(),
+            name_filter: String::new(),
+            scene_name: String::from("scene"),
+            scene_status: String::new(),
+            placement: PlacementOptions::new(),
+            last_selected: None,
+            overlay: OverlaySettings::new(),
+            gizmo: Gizmo::new(),
+            move_mode: MoveMode::new(),
+            snapping: Snapping::new(),
+            array_tool: ArrayTool::new(),
+            mirror: MirrorTool::new(),
+            rigs: RigTool::new(),
+            presets: PresetLibrary::new(),
+            defaults: SpawnDefaults::new(),
+            inspector: MemoryInspector::new(),
+            areas: Vec::new(),
+            area_id_track: 0,
             cursor
         }
     }
@@ -196,106 +283,696 @@ impl LitcherContext {
         Some((pos, rot))
     }
 
+    /// Spawns a new light of the given kind and adds it at the end of the list, returning its
+    /// index.
+    pub fn spawn_light(
+        &mut self,
+        kind: LightKind,
+        pos: Position,
+        rot: RotationMatrix,
+    ) -> Option<usize> {
+        let world = self.player.get_world()?;
+        let defaults = self.defaults.get(kind);
+        let light = unsafe {
+            match kind {
+                LightKind::PointLight => LightType::PointLight(PointLight::new(
+                    self.memory_pools.pointlight.read()?,
+                    pos,
+                    rot,
+                    defaults,
+                    world,
+                )),
+                LightKind::SpotLight => LightType::SpotLight(SpotLight::new(
+                    self.memory_pools.spotlight.read()?,
+                    pos,
+                    rot,
+                    defaults,
+                    world,
+                )),
+            }
+        };
+
+        self.lights.push(LightContainer::new(light, self.id_track));
+        self.id_track += 1;
+        Some(self.lights.len() - 1)
+    }
+
+    /// Spawns a light where the placement options say, and makes it the last selected one so
+    /// spawning several in a row with "At the last selected light" lays them out in a line.
+    pub fn spawn_placed(&mut self, kind: LightKind) -> Option<usize> {
+        let previous = self
+            .last_selected
+            .and_then(|id| self.lights.iter_mut().find(|l| l.id == id))
+            .map(|l| l.get_pos_rot());
+        let (pos, rot) =
+            self.placement
+                .resolve(self.get_pos_rot()?, self.player.get_position(), previous)?;
+
+        let pos = self.snapping.snap_position(pos);
+        let rot = self.snapping.snap_rotation(rot);
+
+        let ix = self.spawn_light(kind, pos, rot)?;
+        self.last_selected = Some(self.lights[ix].id);
+        Some(ix)
+    }
+
+    /// Ids of the lights the tools act on: the selected ones, or the last selected one when
+    /// there's no selection.
+    fn targeted_ids(&self) -> Vec<usize> {
+        let selected: Vec<usize> = self
+            .lights
+            .iter()
+            .filter(|l| l.selected)
+            .map(|l| l.id)
+            .collect();
+
+        if selected.is_empty() {
+            self.last_selected.into_iter().collect()
+        } else {
+            selected
+        }
+    }
+
+    /// Spawns the array tool's copies of the last selected light, in the same group as it.
+    fn spawn_array(&mut self) -> Option<()> {
+        let player = self.player.get_position();
+        let source = self
+            .lights
+            .iter_mut()
+            .find(|l| Some(l.id) == self.last_selected)?;
+
+        let kind = source.light.kind();
+        let group = source.group;
+        let snapshot = source.snapshot();
+        let copies = self.array_tool.copies(
+            source.get_pos_rot(),
+            source.brightness,
+            snapshot.color,
+            player,
+        );
+
+        for copy in copies {
+            let ix = self.spawn_light(kind, copy.pos, copy.rot)?;
+            let light = &mut self.lights[ix];
+            light.apply_snapshot(&snapshot);
+            field(FieldId::Brightness).write(light, copy.brightness);
+            light.color = copy.color;
+            light.group = group;
+        }
+
+        Some(())
+    }
+
+    /// Spawns a mirrored copy of every targeted light.
+    fn mirror_lights(&mut self) -> Option<()> {
+        let plane = self
+            .mirror
+            .plane(self.get_pos_rot()?, self.player.get_position());
+
+        for id in self.targeted_ids() {
+            let Some(source) = self.lights.iter_mut().find(|l| l.id == id) else {
+                continue;
+            };
+
+            let kind = source.light.kind();
+            let snapshot = source.snapshot();
+            let group = source.group;
+            let name = format!("{} (mirror)", source.name);
+            let (pos, rot) = source.get_pos_rot();
+
+            let ix = self.spawn_light(
+                kind,
+                plane.reflect_point(pos),
+                plane.reflect_rotation(rot),
+            )?;
+            let light = &mut self.lights[ix];
+            light.apply_snapshot(&snapshot);
+            light.group = group;
+            light.name = name;
+        }
+
+        Some(())
+    }
+
+    /// Spawns the lights of the current rig as spotlights in a new group.
+    fn spawn_rig(&mut self) -> Option<()> {
+        let camera = self.get_pos_rot()?;
+        let subject = self.rigs.subject(camera, self.player.get_position())?;
+        let placements = self.rigs.placements(subject, camera.0);
+        let group = self.group_by_name(format!(
+            "{} rig {}",
+            self.rigs.kind.label(),
+            self.group_id_track
+        ));
+
+        for placement in placements {
+            let ix = self.spawn_light(LightKind::SpotLight, placement.pos, placement.rot)?;
+            let light = &mut self.lights[ix];
+            light.name = placement.name;
+            field(FieldId::Brightness).write(light, placement.brightness);
+            field(FieldId::Radius).write(light, placement.radius);
+            light.color = placement.color;
+            light.group = Some(group);
+        }
+
+        Some(())
+    }
+
+    fn handle_preset_event(&mut self, event: PresetEvent) {
+        match event {
+            PresetEvent::Spawn(ix) => {
+                let Some(preset) = self.presets.presets.get(ix).cloned() else {
+                    return;
+                };
+                if let Some(ix) = self.spawn_placed(preset.settings.kind) {
+                    let light = &mut self.lights[ix];
+                    light.apply_snapshot(&preset.settings);
+                    light.name = preset.name;
+                }
+            }
+            PresetEvent::SaveSelected => {
+                let Some(light) = self
+                    .lights
+                    .iter_mut()
+                    .find(|l| Some(l.id) == self.last_selected)
+                else {
+                    self.presets.status = String::from("Select a light first");
+                    return;
+                };
+
+                let name = if self.presets.new_name.is_empty() {
+                    light.name.clone()
+                } else {
+                    std::mem::take(&mut self.presets.new_name)
+                };
+                let preset = LightPreset {
+                    name,
+                    settings: light.snapshot(),
+                };
+                self.presets.status = match self.presets.add(preset) {
+                    Ok(()) => format!("Saved to {}", PRESETS_FILE),
+                    Err(e) => format!("Error: {:#}", e),
+                };
+            }
+        }
+    }
+
+    fn spawn_area(&mut self) -> Option<()> {
+        let (pos, rot) =
+            self.placement
+                .resolve(self.get_pos_rot()?, self.player.get_position(), None)?;
+        self.areas.push(AreaLight::new(self.area_id_track, pos, rot));
+        self.area_id_track += 1;
+        Some(())
+    }
+
+    fn remove_area(&mut self, ix: usize) {
+        // Without a world its lights can't be removed, and they'd be left hidden from the list.
+        let Some(world) = self.player.get_world() else {
+            return;
+        };
+        let area = self.areas.remove(ix);
+
+        for id in area.lights {
+            if let Some(light_ix) = self.lights.iter().position(|l| l.id == id) {
+                self.lights.remove(light_ix).remove_light(world);
+            }
+        }
+    }
+
+    fn areas_ui(&mut self, ui: &imgui::Ui) {
+        if ui.button("Spawn area light") {
+            self.spawn_area();
+        }
+
+        let mut event = None;
+        for (ix, area) in self.areas.iter_mut().enumerate() {
+            if let Some(e) = area.handle_ui(ui) {
+                event = Some((ix, e));
+            }
+        }
+
+        match event {
+            Some((ix, AreaEvent::Remove)) => self.remove_area(ix),
+            Some((ix, AreaEvent::MoveToCamera)) => {
+                if let Some((pos, rot)) = self.get_pos_rot() {
+                    self.areas[ix].pos = pos;
+                    self.areas[ix].rot = rot;
+                }
+            }
+            None => (),
+        }
+    }
+
+    /// Spawns or removes point lights so every area has as many as it needs, and moves them to
+    /// where the area says.
+    fn sync_areas(&mut self, world: usize) -> Option<()> {
+        for ix in 0..self.areas.len() {
+            // The game might have deleted some of them.
+            let lights = &self.lights;
+            self.areas[ix]
+                .lights
+                .retain(|id| lights.iter().any(|l| l.id == *id));
+
+            let count = self.areas[ix].count();
+            while self.areas[ix].lights.len() > count {
+                let id = self.areas[ix].lights.pop()?;
+                if let Some(light_ix) = self.lights.iter().position(|l| l.id == id) {
+                    self.lights.remove(light_ix).remove_light(world);
+                }
+            }
+
+            while self.areas[ix].lights.len() < count {
+                let (pos, rot) = (self.areas[ix].pos, self.areas[ix].rot);
+                let light_ix = self.spawn_light(LightKind::PointLight, pos, rot)?;
+                let area = &mut self.areas[ix];
+                let light = &mut self.lights[light_ix];
+                light.area = Some(area.id);
+                light.name = format!("{} {}", area.name, area.lights.len());
+                area.lights.push(light.id);
+            }
+
+            let area = &self.areas[ix];
+            let shadows = if area.shadows { 1.0 } else { 0.0 };
+            for (id, pos) in area.lights.iter().zip(area.positions()) {
+                let Some(light) = self.lights.iter_mut().find(|l| l.id == *id) else {
+                    continue;
+                };
+
+                light.set_pos_rot(pos, area.rot);
+                light.color = area.color;
+                field(FieldId::Brightness).write(light, area.light_brightness());
+                field(FieldId::Radius).write(light, area.radius);
+                light.enabled = area.enabled;
+                field(FieldId::ShadowCastingMode).write(light, shadows);
+            }
+        }
+
+        Some(())
+    }
+
+    fn snapping_ui(&mut self, ui: &imgui::Ui) {
+        self.snapping.handle_ui(ui);
+
+        let mut snap_now = false;
+        let mut height = None;
+        if ui.button("Snap now") {
+            snap_now = true;
+        }
+        ui.same_line();
+        if ui.button("Align to camera height") {
+            height = self.get_pos_rot().map(|(pos, _)| pos.z);
+        }
+        ui.same_line();
+        if ui.button("Drop to player feet") {
+            height = self.player.get_position().map(|pos| pos.z);
+        }
+
+        if !snap_now && height.is_none() {
+            return;
+        }
+
+        let targets = self.targeted_ids();
+        for light in self.lights.iter_mut().filter(|l| targets.contains(&l.id)) {
+            let (mut pos, mut rot) = light.get_pos_rot();
+            if let Some(z) = height {
+                pos.z = z;
+            }
+            if snap_now {
+                pos = self.snapping.snap_position(pos);
+                rot = self.snapping.snap_rotation(rot);
+            }
+            light.set_pos_rot(pos, rot);
+        }
+    }
+
+    /// The settings to paste. Whatever is on the system clipboard wins if it's a valid light, so
+    /// settings shared by someone else can be pasted directly.
+    fn settings_to_paste(&self, ui: &imgui::Ui) -> Option<LightSnapshot> {
+        ui.clipboard_text()
+            .and_then(|text| LightSnapshot::from_text(&text).ok())
+            .or_else(|| self.copied.clone())
+    }
+
+    fn handle_light_action(&mut self, ui: &imgui::Ui, ix: usize, action: LightAction) {
+        match action {
+            LightAction::Duplicate => {
+                let light = &mut self.lights[ix];
+                let kind = light.light.kind();
+                let snapshot = light.snapshot();
+                let (pos, rot) = light.get_pos_rot();
+                if let Some(new_ix) = self.spawn_light(kind, pos, rot) {
+                    self.lights[new_ix].apply_snapshot(&snapshot);
+                }
+            }
+            LightAction::Copy => {
+                let snapshot = self.lights[ix].snapshot();
+                ui.set_clipboard_text(snapshot.to_text());
+                self.copied = Some(snapshot);
+            }
+            LightAction::Paste => {
+                if let Some(snapshot) = self.settings_to_paste(ui) {
+                    self.lights[ix].apply_snapshot(&snapshot);
+                }
+            }
+            LightAction::Select => {
+                self.last_selected = Some(self.lights[ix].id);
+            }
+            LightAction::Remove => {
+                let light = self.lights.remove(ix);
+                if let Some(world) = self.player.get_world() {
+                    light.remove_light(world);
+                }
+            }
+            LightAction::MoveHere(id) => {
+                let Some(from) = self.lights.iter().position(|l| l.id == id) else {
+                    return;
+                };
+                if from == ix {
+                    return;
+                }
+
+                // The moved light joins the group of the light it was dropped on.
+                let group = self.lights[ix].group;
+                let mut light = self.lights.remove(from);
+                light.group = group;
+                // Removing it shifted everything after it one slot up, the target included.
+                let to = if from < ix { ix - 1 } else { ix };
+                self.lights.insert(to, light);
+            }
+        }
+    }
+
+    /// Creates a new group with the currently selected lights in it.
+    fn group_selected(&mut self) {
+        let name = if self.new_group_name.is_empty() {
+            format!("Group {}", self.group_id_track)
+        } else {
+            std::mem::take(&mut self.new_group_name)
+        };
+
+        let group = LightGroup::new(self.group_id_track, name);
+        self.group_id_track += 1;
+        self.lights
+            .iter_mut()
+            .filter(|l| l.selected)
+            .for_each(|l| l.group = Some(group.id));
+        self.groups.push(group);
+    }
+
+    /// Returns the id of the group with the given name, creating it if there's none.
+    fn group_by_name(&mut self, name: String) -> usize {
+        if let Some(group) = self.groups.iter().find(|g| g.name == name) {
+            return group.id;
+        }
+
+        let group = LightGroup::new(self.group_id_track, name);
+        self.group_id_track += 1;
+        let id = group.id;
+        self.groups.push(group);
+        id
+    }
+
+    fn save_scene(&mut self) -> Result<std::path::PathBuf> {
+        let groups = &self.groups;
+        // The lights of an area get saved through the area, which spawns them again on load.
+        let lights = self
+            .lights
+            .iter_mut()
+            .filter(|light| light.area.is_none())
+            .map(|light| {
+                let (pos, rot) = light.get_pos_rot();
+                let group = light
+                    .group
+                    .and_then(|id| groups.iter().find(|g| g.id == id))
+                    .map(|g| g.name.clone());
+
+                SceneLight {
+                    name: light.name.clone(),
+                    group,
+                    pos,
+                    rot,
+                    settings: light.snapshot(),
+                }
+            })
+            .collect();
+
+        Scene {
+            lights,
+            areas: self.areas.clone(),
+            exposure: Some(self.tonemapping.settings()),
+        }
+        .save(&self.scene_name)
+    }
+
+    /// Spawns every light and area of the scene, on top of the ones we already have. Returns how
+    /// many lights and areas it added.
+    fn load_scene(&mut self) -> Result<(usize, usize)> {
+        let scene = Scene::load(&self.scene_name)?;
+        if let Some(exposure) = scene.exposure {
+            self.tonemapping.apply_settings(exposure);
+        }
+
+        if self.player.get_world().is_none() {
+            bail!("Couldn't spawn the lights, are you in the world?");
+        }
+
+        let count = (scene.lights.len(), scene.areas.len());
+        for mut area in scene.areas {
+            // Its lights get spawned by `sync_areas`.
+            area.id = self.area_id_track;
+            self.area_id_track += 1;
+            self.areas.push(area);
+        }
+
+        for scene_light in scene.lights {
+            let ix = self
+                .spawn_light(scene_light.settings.kind, scene_light.pos, scene_light.rot)
+                .context("Couldn't spawn the light, are you in the world?")?;
+            let group = scene_light.group.map(|name| self.group_by_name(name));
+
+            let light = &mut self.lights[ix];
+            light.apply_snapshot(&scene_light.settings);
+            light.enabled = scene_light.settings.enabled;
+            light.name = scene_light.name;
+            light.group = group;
+        }
+
+        Ok(count)
+    }
+
+    fn scene_ui(&mut self, ui: &imgui::Ui) {
+        ui.set_next_item_width(150.0);
+        ui.input_text("##scene_name", &mut self.scene_name)
+            .hint("Scene name")
+            .build();
+        ui.same_line();
+        if ui.button("Save scene") {
+            self.scene_status = match self.save_scene() {
+                Ok(path) => format!("Saved to {}", path.display()),
+                Err(e) => format!("Error: {:#}", e),
+            };
+        }
+        ui.same_line();
+        if ui.button("Load scene") {
+            self.scene_status = match self.load_scene() {
+                Ok((lights, 0)) => format!("Loaded {} lights", lights),
+                Ok((lights, areas)) => format!("Loaded {} lights and {} areas", lights, areas),
+                Err(e) => format!("Error: {:#}", e),
+            };
+        }
+
+        if !self.scene_status.is_empty() {
+            ui.text_wrapped(&self.scene_status);
+        }
+        ui.separator();
+    }
+
+    /// Draws the list of lights, the ungrouped ones first and then every group in its own
+    /// collapsible section.
+    fn light_list(&mut self, ui: &imgui::Ui) {
+        let mut row_action = None;
+        let mut group_event = None;
+
+        ui.input_text("Filter", &mut self.name_filter)
+            .hint("Search by name")
+            .build();
+
+        for (i, light) in self.lights.iter_mut().enumerate() {
+            if light.group.is_some()
+                || light.area.is_some()
+                || !light.matches_filter(&self.name_filter)
+            {
+                continue;
+            }
+
+            if let Some(action) = light.render_row(ui) {
+                row_action = Some((i, action));
+            }
+        }
+
+        for group in self.groups.iter_mut() {
+            let id = ui.push_id_usize(group.id);
+            let (open, event) = group.render_header(ui);
+            if let Some(event) = event {
+                group_event = Some((group.id, event));
+            }
+
+            if open {
+                ui.indent();
+                for (i, light) in self.lights.iter_mut().enumerate() {
+                    if light.group != Some(group.id)
+                        || light.area.is_some()
+                        || !light.matches_filter(&self.name_filter)
+                    {
+                        continue;
+                    }
+
+                    if let Some(action) = light.render_row(ui) {
+                        row_action = Some((i, action));
+                    }
+                }
+                ui.unindent();
+            }
+            id.end();
+        }
+
+        if let Some((group_id, event)) = group_event {
+            match event {
+                GroupEvent::LightDropped(id) => {
+                    if let Some(light) = self.lights.iter_mut().find(|l| l.id == id) {
+                        light.group = Some(group_id);
+                    }
+                }
+                GroupEvent::SelectAll => self
+                    .lights
+                    .iter_mut()
+                    .for_each(|l| l.selected = l.group == Some(group_id)),
+                GroupEvent::Ungroup => {
+                    self.lights
+                        .iter_mut()
+                        .filter(|l| l.group == Some(group_id))
+                        .for_each(|l| l.group = None);
+                    self.groups.retain(|g| g.id != group_id);
+                }
+            }
+        }
+
+        if let Some((ix, action)) = row_action {
+            self.handle_light_action(ui, ix, action);
+        }
+    }
+
     pub fn main_window(&mut self, ui: &mut imgui::Ui) {
         ui.window(VERSION)
             .size([410.0, 200.0], Condition::FirstUseEver)
             .build(|| {
                 self.tonemapping.handle_ui(ui);
+                self.master.handle_ui(ui);
+                self.overlay.handle_ui(ui);
+                self.gizmo.handle_ui(ui);
+                self.move_mode.handle_ui(ui);
+                ui.separator();
+                self.snapping_ui(ui);
+                ui.separator();
+
+                if ui.collapsing_header("Array tool", TreeNodeFlags::empty())
+                    && self.array_tool.handle_ui(ui)
+                {
+                    self.spawn_array();
+                }
+
+                if ui.collapsing_header("Mirror", TreeNodeFlags::empty())
+                    && self.mirror.handle_ui(ui)
+                {
+                    self.mirror_lights();
+                }
+
+                if ui.collapsing_header("Area lights", TreeNodeFlags::empty()) {
+                    ui.indent();
+                    self.areas_ui(ui);
+                    ui.unindent();
+                }
+
+                if ui.collapsing_header("Lighting rigs", TreeNodeFlags::empty())
+                    && self.rigs.handle_ui(ui)
+                {
+                    self.spawn_rig();
+                }
+                self.placement.handle_ui(ui);
                 if ui.button("Spawn new pointlight") {
-                    if let (Some((pos, rot)), Some(world)) =
-                        (self.get_pos_rot(), self.player.get_world())
-                    {
-                        unsafe {
-                            let light = LightContainer::new(
-                                LightType::PointLight(PointLight::new(
-                                    self.memory_pools.pointlight.read().unwrap(),
-                                    pos,
-                                    rot,
-                                    world,
-                                )),
-                                self.id_track,
-                            );
-                            self.id_track += 1;
-                            self.lights.push(light);
-                        }
-                    }
+                    self.spawn_placed(LightKind::PointLight);
                 }
 
                 if ui.button("Spawn new spotlight") {
-                    if let (Some((pos, rot)), Some(world)) =
-                        (self.get_pos_rot(), self.player.get_world())
-                    {
-                        unsafe {
-                            let light = LightContainer::new(
-                                LightType::SpotLight(SpotLight::new(
-                                    self.memory_pools.spotlight.read().unwrap(),
-                                    pos,
-                                    rot,
-                                    world,
-                                )),
-                                self.id_track,
-                            );
-                            self.id_track += 1;
-                            self.lights.push(light);
-                        }
-                    }
+                    self.spawn_placed(LightKind::SpotLight);
                 }
 
-                ui.separator();
-
-                let world = if let Some(world) = self.player.get_world() {
-                    world
-                } else {
-                    return;
-                };
+                if ui.collapsing_header("Defaults", TreeNodeFlags::empty()) {
+                    ui.indent();
+                    self.defaults.handle_ui(ui);
+                    ui.unindent();
+                }
 
-                let mut light_to_remove = None;
-                self.lights.iter_mut().enumerate().for_each(|(i, light)| {
-                    let id = ui.push_id(&light.id);
-                    if ui.button("X") {
-                        light_to_remove = Some(i);
-                    }
-                    ui.same_line();
-                    ui.text(&light.id);
-                    ui.same_line();
-                    ui.color_button_config("Color of light ##", light.color)
-                        .flags(ColorEditFlags::NO_INPUTS | ColorEditFlags::NO_LABEL)
-                        .build();
-                    ui.same_line();
-                    if ui.button("Edit") {
-                        light.open = true;
+                if ui.collapsing_header("Presets", TreeNodeFlags::empty()) {
+                    if let Some(event) = self.presets.handle_ui(ui) {
+                        self.handle_preset_event(event);
                     }
-                    ui.same_line();
+                }
 
-                    let inner_light = light.light.get_light_mut();
-                    if ui.checkbox("on/off", &mut inner_light.is_enabled) {
-                        light.update_render(world);
-                    }
+                ui.checkbox("Memory inspector", &mut self.inspector.open);
 
-                    ui.same_line();
-                    ui.checkbox("Attach to camera", &mut light.attach_camera);
+                if ui.collapsing_header("Hooks", TreeNodeFlags::empty()) {
+                    ui.indent();
+                    self.hooks.handle_ui(ui);
+                    ui.unindent();
+                }
 
-                    id.end();
-                });
+                ui.separator();
 
-                if let Some(ix) = light_to_remove {
-                    println!("Light to remove: {}", ix);
-                    let light = self.lights.remove(ix);
-                    light.remove_light(world);
+                if self.player.get_world().is_none() {
+                    return;
                 }
 
+                self.scene_ui(ui);
+                self.light_list(ui);
+
                 if self.lights.len() == 0 {
                     return;
                 }
 
                 ui.separator();
 
+                ui.set_next_item_width(150.0);
+                ui.input_text("##new_group_name", &mut self.new_group_name)
+                    .hint("Group name")
+                    .build();
+                ui.same_line();
+                if ui.button("Group selected") {
+                    self.group_selected();
+                }
+                ui.same_line();
+                if ui.button("Clear selection") {
+                    self.lights.iter_mut().for_each(|l| l.selected = false);
+                }
+
+                self.batch.handle_ui(ui, &mut self.lights);
+
+                if ui.button("Paste to selected lights") {
+                    if let Some(snapshot) = self.settings_to_paste(ui) {
+                        self.lights
+                            .iter_mut()
+                            .filter(|light| light.selected)
+                            .for_each(|light| light.apply_snapshot(&snapshot));
+                    }
+                }
 
                 if ui.button("Delete all lights") {
                     if let Some(world) = self.player.get_world() {
                         self.lights.drain(..).for_each(|light| {
                             light.remove_light(world);
                         });
+                        self.areas.clear();
                         self.player.updated();
                     }
                 }
@@ -314,6 +991,7 @@ impl ImguiRenderLoop for LitcherContext {
         let _world = self.player.get_world();
         if _world.is_none() {
             self.lights.clear();
+            self.areas.clear();
             self.player.updated();
 
             println!("World is changed or none");
@@ -323,6 +1001,7 @@ impl ImguiRenderLoop for LitcherContext {
         if self.player.should_update() {
             println!("Player was updated");
             self.lights.clear();
+            self.areas.clear();
             self.player.updated();
         }
 
@@ -333,7 +1012,14 @@ impl ImguiRenderLoop for LitcherContext {
             self.cursor.disable_cursor();
         }
 
+        if !ui.io().want_capture_keyboard && ui.is_key_pressed_no_repeat(MOVE_MODE_KEY) {
+            self.move_mode.active = !self.move_mode.active;
+        }
+
+        self.tonemapping.update(ui, &mut self.hooks);
+
         if cfg!(debug_assertions) && ui.is_key_pressed_no_repeat(imgui::Key::F6) {
+            self.hooks.remove_all();
             hudhook::eject();
         }
 
@@ -347,18 +1033,96 @@ impl ImguiRenderLoop for LitcherContext {
 
         if self.show {
             ui.set_mouse_cursor(Some(imgui::MouseCursor::Arrow));
+
+            if let Some((pos, rot)) = self.get_pos_rot() {
+                let projection = self.overlay.projection(pos, rot, ui);
+                let clicked = self.overlay.draw(
+                    ui,
+                    &projection,
+                    &mut self.lights,
+                    &self.master,
+                    self.last_selected,
+                );
+
+                // The gizmo is drawn after the markers so it stays on top, and it takes the
+                // clicks when the mouse is over one of its handles.
+                let selected = self
+                    .last_selected
+                    .and_then(|id| self.lights.iter_mut().find(|l| l.id == id));
+                let on_gizmo = match selected {
+                    Some(light) => {
+                        // The lights of an area get moved through it: the light stands in for
+                        // the area's center and the area takes wherever it's left, then
+                        // `sync_areas` puts it back in its place.
+                        let mut area = light
+                            .area
+                            .and_then(|id| self.areas.iter_mut().find(|a| a.id == id));
+                        if let Some(area) = &area {
+                            light.set_pos_rot(area.pos, area.rot);
+                            field(FieldId::Radius).write(light, area.radius);
+                        }
+
+                        self.move_mode.update(ui, rot, &self.snapping, light);
+                        let on_gizmo = self.gizmo.update(
+                            ui,
+                            &projection,
+                            &self.snapping,
+                            &self.master,
+                            light,
+                        );
+
+                        if let Some(area) = &mut area {
+                            (area.pos, area.rot) = light.get_pos_rot();
+                            area.radius = light.radius;
+                        }
+                        on_gizmo
+                    }
+                    None => false,
+                };
+
+                if let Some(ix) = clicked.filter(|_| !on_gizmo) {
+                    self.lights[ix].open = true;
+                    self.handle_light_action(ui, ix, LightAction::Select);
+                }
+            }
+
             self.main_window(ui);
             self.cursor.enable_cursor();
 
-            self.lights.iter_mut().for_each(|lw| lw.render_window(ui));
+            let camera = self.get_pos_rot().map(|(_, rot)| rot);
+            let mut actions = Vec::new();
+            for (i, lw) in self.lights.iter_mut().enumerate() {
+                if let Some(action) = lw.render_window(ui, camera) {
+                    actions.push((i, action));
+                }
+            }
+
+            for (i, action) in actions {
+                self.handle_light_action(ui, i, action);
+            }
+
+            if let Some(world) = self.player.get_world() {
+                let selected = self
+                    .last_selected
+                    .and_then(|id| self.lights.iter_mut().find(|l| l.id == id));
+                self.inspector.render(ui, selected, world);
+            }
         } 
 
+        if let Some(world) = self.player.get_world() {
+            self.sync_areas(world);
+        }
+
         if let (Some((pos, rot)), Some(world)) = (self.get_pos_rot(), self.player.get_world()) {
+            let any_solo = self.lights.iter().any(|l| l.solo);
             for light_wrapper in self.lights.iter_mut() {
                 if light_wrapper.attach_camera {
                     light_wrapper.set_pos_rot(pos, rot);
                 }
 
+                let group_enabled = is_group_enabled(&self.groups, light_wrapper.group);
+                light_wrapper.apply_master(&self.master, any_solo, group_enabled);
+
                 match &mut light_wrapper.light {
                     LightType::PointLight(pl) => {
                         pl.update_render(world);
@@ -371,6 +1135,14 @@ impl ImguiRenderLoop for LitcherContext {
         }
     }
 
+    /// While the move mode is on, the keys are for moving the light, not for Geralt.
+    fn message_filter(&self, _io: &imgui::Io) -> MessageFilter {
+        if self.show && self.move_mode.active {
+            MessageFilter::InputAll
+        } else {
+            MessageFilter::empty()
+        }
+    }
 }
 
 use hudhook::*;
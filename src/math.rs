@@ -0,0 +1,208 @@
+//! Small vector helpers over the game's own types. The game uses a right handed coordinate
+//! system with Z pointing up.
+use std::ops::{Add, Mul, Neg, Sub};
+
+use crate::definitions::*;
+
+impl Position {
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn dot(self, other: Self) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalized(self) -> Self {
+        let len = self.length();
+        if len <= f32::EPSILON {
+            return self;
+        }
+        self * (1.0 / len)
+    }
+
+    /// Rotates the vector around `axis` (which has to be normalized) by `angle` radians.
+    pub fn rotated(self, axis: Position, angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        self * cos + axis.cross(self) * sin + axis * (axis.dot(self) * (1.0 - cos))
+    }
+}
+
+impl Add for Position {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl Sub for Position {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl Mul<f32> for Position {
+    type Output = Self;
+    fn mul(self, rhs: f32) -> Self {
+        Self::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+impl Neg for Position {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z)
+    }
+}
+
+/// These helpers read the matrix as three rows of four floats (the fourth one being padding),
+/// taking the rows as the right, forward and up axes of the object in world space.
+impl RotationMatrix {
+    fn row(&self, i: usize) -> Position {
+        let m: [f32; 12] = (*self).into();
+        Position::new(m[i * 4], m[i * 4 + 1], m[i * 4 + 2])
+    }
+
+    pub fn right(&self) -> Position {
+        self.row(0)
+    }
+
+    pub fn forward(&self) -> Position {
+        self.row(1)
+    }
+
+    pub fn up(&self) -> Position {
+        self.row(2)
+    }
+
+    #[rustfmt::skip]
+    pub fn from_axes(right: Position, forward: Position, up: Position) -> Self {
+        [
+            right.x, right.y, right.z, 0.0,
+            forward.x, forward.y, forward.z, 0.0,
+            up.x, up.y, up.z, 0.0,
+        ]
+        .into()
+    }
+
+    /// Rotates the three axes around the world space `axis` by `angle` radians.
+    pub fn rotated(&self, axis: Position, angle: f32) -> Self {
+        Self::from_axes(
+            self.right().rotated(axis, angle),
+            self.forward().rotated(axis, angle),
+            self.up().rotated(axis, angle),
+        )
+    }
+
+    /// Builds the rotation from yaw (around the world's Z), pitch (around the right axis) and roll
+    /// (around the forward axis), in radians. Yaw 0 looks to +Y.
+    pub fn from_euler(yaw: f32, pitch: f32, roll: f32) -> Self {
+        let x = Position::new(1.0, 0.0, 0.0);
+        let y = Position::new(0.0, 1.0, 0.0);
+        let z = Position::new(0.0, 0.0, 1.0);
+        Self::from_axes(x, y, z)
+            .rotated(y, roll)
+            .rotated(x, pitch)
+            .rotated(z, yaw)
+    }
+
+    /// The inverse of `from_euler`, returns `[yaw, pitch, roll]` in radians.
+    pub fn to_euler(&self) -> [f32; 3] {
+        let forward = self.forward();
+        let yaw = (-forward.x).atan2(forward.y);
+        let pitch = forward.z.clamp(-1.0, 1.0).asin();
+        let roll = (-self.right().z).atan2(self.up().z);
+        [yaw, pitch, roll]
+    }
+
+    /// Rotation whose forward axis points to `dir`, keeping the right axis horizontal.
+    pub fn look_at(dir: Position) -> Self {
+        let forward = dir.normalized();
+        let world_up = Position::new(0.0, 0.0, 1.0);
+        let mut right = forward.cross(world_up);
+        if right.length() <= 1e-4 {
+            // Looking straight up or down, any horizontal axis works.
+            right = Position::new(1.0, 0.0, 0.0);
+        }
+        let right = right.normalized();
+        let up = right.cross(forward);
+        Self::from_axes(right, forward, up)
+    }
+}
+
+/// Rotates the hue of an RGBA color by `degrees`, keeping saturation, value and alpha.
+pub fn shift_hue(color: [f32; 4], degrees: f32) -> [f32; 4] {
+    let [r, g, b, a] = color;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    if delta <= f32::EPSILON {
+        return color;
+    }
+
+    let hue = if max == r {
+        60.0 * ((g - b) / delta)
+    } else if max == g {
+        60.0 * ((b - r) / delta) + 120.0
+    } else {
+        60.0 * ((r - g) / delta) + 240.0
+    };
+    let hue = (hue + degrees).rem_euclid(360.0);
+
+    let x = delta * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let (r, g, b) = match (hue / 60.0) as u32 {
+        0 => (delta, x, 0.0),
+        1 => (x, delta, 0.0),
+        2 => (0.0, delta, x),
+        3 => (0.0, x, delta),
+        4 => (x, 0.0, delta),
+        _ => (delta, 0.0, x),
+    };
+    [r + min, g + min, b + min, a]
+}
+
+/// Approximate RGB color of a black body at the given temperature in Kelvin, useful to pick
+/// light colors the way photographers do. Based on Tanner Helland's fit.
+pub fn kelvin_to_rgb(kelvin: f32) -> [f32; 4] {
+    let t = kelvin.clamp(1000.0, 40000.0) / 100.0;
+
+    let red = if t <= 66.0 {
+        255.0
+    } else {
+        329.698_73 * (t - 60.0).powf(-0.133_204_76)
+    };
+
+    let green = if t <= 66.0 {
+        99.470_8 * t.ln() - 161.119_57
+    } else {
+        288.122_16 * (t - 60.0).powf(-0.075_514_846)
+    };
+
+    let blue = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.517_73 * (t - 10.0).ln() - 305.044_8
+    };
+
+    [
+        (red / 255.0).clamp(0.0, 1.0),
+        (green / 255.0).clamp(0.0, 1.0),
+        (blue / 255.0).clamp(0.0, 1.0),
+        1.0,
+    ]
+}
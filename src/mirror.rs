@@ -0,0 +1,109 @@
+use crate::definitions::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MirrorPlane {
+    /// Vertical plane through the camera, splitting the view in left and right.
+    Camera,
+    /// Same as the camera one but going through the player, for symmetric portrait setups.
+    Player,
+    WorldX,
+    WorldY,
+    WorldZ,
+}
+
+impl MirrorPlane {
+    const ALL: [Self; 5] = [
+        Self::Camera,
+        Self::Player,
+        Self::WorldX,
+        Self::WorldY,
+        Self::WorldZ,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Camera => "Camera (left/right of the view)",
+            Self::Player => "Player (left/right of the view)",
+            Self::WorldX => "World X through the player",
+            Self::WorldY => "World Y through the player",
+            Self::WorldZ => "World Z through the player",
+        }
+    }
+}
+
+/// A plane given by a point on it and its normal.
+#[derive(Copy, Clone)]
+pub struct Plane {
+    pub origin: Position,
+    pub normal: Position,
+}
+
+impl Plane {
+    fn reflect_vector(&self, v: Position) -> Position {
+        v - self.normal * (2.0 * self.normal.dot(v))
+    }
+
+    pub fn reflect_point(&self, p: Position) -> Position {
+        self.origin + self.reflect_vector(p - self.origin)
+    }
+
+    /// Reflecting the three axes would give a left handed matrix, which the game can't use. We
+    /// flip the right axis back so the light keeps pointing (forward) and its up where the
+    /// reflection says.
+    pub fn reflect_rotation(&self, rot: RotationMatrix) -> RotationMatrix {
+        RotationMatrix::from_axes(
+            -self.reflect_vector(rot.right()),
+            self.reflect_vector(rot.forward()),
+            self.reflect_vector(rot.up()),
+        )
+    }
+}
+
+pub struct MirrorTool {
+    pub plane: MirrorPlane,
+}
+
+impl MirrorTool {
+    pub fn new() -> Self {
+        Self {
+            plane: MirrorPlane::Player,
+        }
+    }
+
+    /// Returns true when the user asked to mirror the lights.
+    pub fn handle_ui(&mut self, ui: &imgui::Ui) -> bool {
+        let mut current = MirrorPlane::ALL
+            .iter()
+            .position(|p| *p == self.plane)
+            .unwrap_or(0);
+        if ui.combo("Mirror plane", &mut current, &MirrorPlane::ALL, |p| {
+            p.label().into()
+        }) {
+            self.plane = MirrorPlane::ALL[current];
+        }
+
+        ui.button("Mirror selected lights")
+    }
+
+    /// The plane to mirror across. The player ones fall back to the camera's position when
+    /// there's no player.
+    pub fn plane(&self, camera: (Position, RotationMatrix), player: Option<Position>) -> Plane {
+        let (camera_pos, camera_rot) = camera;
+        let player = player.unwrap_or(camera_pos);
+
+        // Keep it vertical even if the camera is tilted.
+        let mut camera_right = camera_rot.right();
+        camera_right.z = 0.0;
+        let camera_right = camera_right.normalized();
+
+        let (origin, normal) = match self.plane {
+            MirrorPlane::Camera => (camera_pos, camera_right),
+            MirrorPlane::Player => (player, camera_right),
+            MirrorPlane::WorldX => (player, Position::new(1.0, 0.0, 0.0)),
+            MirrorPlane::WorldY => (player, Position::new(0.0, 1.0, 0.0)),
+            MirrorPlane::WorldZ => (player, Position::new(0.0, 0.0, 1.0)),
+        };
+
+        Plane { origin, normal }
+    }
+}
@@ -0,0 +1,55 @@
+/// Scene-wide controls that work like the master section of an audio mixer. Every light keeps its
+/// own values in its `LightContainer`, and what ends up in the game's memory is those values
+/// scaled by these ones, so the whole scene can be rebalanced (e.g. after an exposure change)
+/// without touching each light.
+pub struct MasterControls {
+    pub brightness: f32,
+    pub tint: [f32; 4],
+    pub radius_scale: f32,
+}
+
+impl MasterControls {
+    pub fn new() -> Self {
+        Self {
+            brightness: 1.0,
+            tint: [1.0; 4],
+            radius_scale: 1.0,
+        }
+    }
+
+    pub fn apply_brightness(&self, brightness: f32) -> f32 {
+        brightness * self.brightness
+    }
+
+    pub fn apply_radius(&self, radius: f32) -> f32 {
+        radius * self.radius_scale
+    }
+
+    /// The light's own radius that ends up as `radius` once the master scale is applied.
+    pub fn remove_radius(&self, radius: f32) -> f32 {
+        radius / self.radius_scale
+    }
+
+    pub fn apply_color(&self, color: [f32; 4]) -> [f32; 4] {
+        [
+            color[0] * self.tint[0],
+            color[1] * self.tint[1],
+            color[2] * self.tint[2],
+            color[3] * self.tint[3],
+        ]
+    }
+
+    pub fn handle_ui(&mut self, ui: &imgui::Ui) {
+        ui.slider_config("Master brightness", 0.0, 10.0)
+            .flags(imgui::SliderFlags::LOGARITHMIC)
+            .build(&mut self.brightness);
+        ui.slider_config("Master radius", 0.1, 10.0)
+            .flags(imgui::SliderFlags::LOGARITHMIC)
+            .build(&mut self.radius_scale);
+        ui.color_edit4("Master tint", &mut self.tint);
+        if ui.button("Reset master") {
+            *self = Self::new();
+        }
+        ui.separator();
+    }
+}
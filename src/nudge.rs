@@ -0,0 +1,164 @@
+use std::borrow::Cow;
+
+use imgui::Key;
+
+use crate::definitions::*;
+use crate::snapping::Snapping;
+
+/// Key bindings for the move mode. Movement happens along the camera's axes.
+#[derive(Copy, Clone, Debug)]
+pub struct MoveKeys {
+    pub forward: Key,
+    pub back: Key,
+    pub left: Key,
+    pub right: Key,
+    pub up: Key,
+    pub down: Key,
+}
+
+impl MoveKeys {
+    fn bindings(&mut self) -> [(&'static str, &mut Key); 6] {
+        [
+            ("Forward", &mut self.forward),
+            ("Back", &mut self.back),
+            ("Left", &mut self.left),
+            ("Right", &mut self.right),
+            ("Up", &mut self.up),
+            ("Down", &mut self.down),
+        ]
+    }
+}
+
+/// While active, the selected light moves relative to where the camera is looking (and the
+/// arrow keys rotate spotlights), since the world axes mean nothing when looking at the scene
+/// from an arbitrary angle. The game doesn't get any input meanwhile.
+pub struct MoveMode {
+    pub active: bool,
+    pub keys: MoveKeys,
+    /// Meters per second.
+    pub speed: f32,
+    /// Degrees per second.
+    pub rotation_speed: f32,
+}
+
+pub const MOVE_MODE_KEY: Key = Key::F7;
+
+impl MoveMode {
+    pub fn new() -> Self {
+        Self {
+            active: false,
+            keys: MoveKeys {
+                forward: Key::W,
+                back: Key::S,
+                left: Key::A,
+                right: Key::D,
+                up: Key::E,
+                down: Key::Q,
+            },
+            speed: 1.0,
+            rotation_speed: 45.0,
+        }
+    }
+
+    pub fn handle_ui(&mut self, ui: &imgui::Ui) {
+        ui.checkbox("Move mode (F7)", &mut self.active);
+        if ui.is_item_hovered() {
+            ui.tooltip_text(
+                "Moves the last selected light with the keys relative to the camera.\n\
+                Arrows rotate spotlights, Shift goes faster and Ctrl slower.",
+            );
+        }
+
+        if !self.active {
+            return;
+        }
+
+        ui.slider_config("Move speed", 0.01, 20.0)
+            .flags(imgui::SliderFlags::LOGARITHMIC)
+            .build(&mut self.speed);
+        ui.slider_config("Rotation speed", 1.0, 180.0)
+            .build(&mut self.rotation_speed);
+
+        if let Some(_node) = ui.tree_node("Key bindings") {
+            for (label, key) in self.keys.bindings() {
+                let mut current = Key::VARIANTS.iter().position(|k| *k == *key).unwrap_or(0);
+                if ui.combo(label, &mut current, &Key::VARIANTS, |k| {
+                    Cow::Owned(format!("{:?}", k))
+                }) {
+                    *key = Key::VARIANTS[current];
+                }
+            }
+        }
+    }
+
+    /// Moves and rotates the light with whatever keys are held this frame. With snapping on, every
+    /// key press moves one grid cell or one angle step instead.
+    pub fn update(
+        &self,
+        ui: &imgui::Ui,
+        camera_rot: RotationMatrix,
+        snapping: &Snapping,
+        light: &mut LightContainer,
+    ) {
+        let io = ui.io();
+        if !self.active || light.attach_camera || io.want_text_input {
+            return;
+        }
+
+        let mut modifier = 1.0;
+        if io.key_shift {
+            modifier *= 4.0;
+        }
+        if io.key_ctrl {
+            modifier *= 0.25;
+        }
+
+        // When stepping, only key presses (and their repeats) count instead of held keys.
+        let axis = |step: bool, positive: Key, negative: Key| {
+            let down = |key| {
+                if step {
+                    ui.is_key_pressed(key)
+                } else {
+                    ui.is_key_down(key)
+                }
+            };
+            (down(positive) as i32 - down(negative) as i32) as f32
+        };
+
+        let keys = &self.keys;
+        let step = snapping.grid_enabled;
+        let direction = camera_rot.right() * axis(step, keys.right, keys.left)
+            + camera_rot.forward() * axis(step, keys.forward, keys.back)
+            + camera_rot.up() * axis(step, keys.up, keys.down);
+
+        let (mut pos, mut rot) = light.get_pos_rot();
+        if snapping.grid_enabled {
+            pos = snapping.step_along(pos, direction);
+        } else {
+            pos = pos + direction * (self.speed * io.delta_time * modifier);
+        }
+
+        if matches!(light.light, LightType::SpotLight(_)) {
+            let step = snapping.angle_enabled;
+            let angle = if step {
+                snapping.angle_step.to_radians()
+            } else {
+                self.rotation_speed.to_radians() * io.delta_time * modifier
+            };
+
+            let yaw = axis(step, Key::LeftArrow, Key::RightArrow);
+            let pitch = axis(step, Key::UpArrow, Key::DownArrow);
+            if yaw != 0.0 {
+                rot = rot.rotated(Position::new(0.0, 0.0, 1.0), yaw * angle);
+            }
+            if pitch != 0.0 {
+                rot = rot.rotated(rot.right(), pitch * angle);
+            }
+            if snapping.angle_enabled && (yaw != 0.0 || pitch != 0.0) {
+                rot = snapping.snap_rotation(rot);
+            }
+        }
+
+        light.set_pos_rot(pos, rot);
+    }
+}
@@ -0,0 +1,184 @@
+use imgui::MouseButton;
+
+use crate::definitions::*;
+use crate::mixer::MasterControls;
+
+// Anything closer than this to the camera doesn't get projected.
+const NEAR_PLANE: f32 = 0.05;
+const CONE_SEGMENTS: usize = 24;
+
+/// Projects world positions to the screen using the game's camera. We don't read the game's FOV,
+/// so it's user configurable; once the markers stick to the lights it's right.
+#[derive(Copy, Clone)]
+pub struct Projection {
+    pub pos: Position,
+    pub rot: RotationMatrix,
+    /// Vertical FOV, in degrees.
+    pub fov: f32,
+    pub screen: [f32; 2],
+}
+
+impl Projection {
+    fn focal_length(&self) -> f32 {
+        (self.screen[1] * 0.5) / (self.fov.to_radians() * 0.5).tan()
+    }
+
+    /// Where on the screen `p` is, or None if it's behind the camera.
+    pub fn project(&self, p: Position) -> Option<[f32; 2]> {
+        let d = p - self.pos;
+        let depth = d.dot(self.rot.forward());
+        if depth < NEAR_PLANE {
+            return None;
+        }
+
+        let f = self.focal_length() / depth;
+        Some([
+            self.screen[0] * 0.5 + d.dot(self.rot.right()) * f,
+            self.screen[1] * 0.5 - d.dot(self.rot.up()) * f,
+        ])
+    }
+
+    /// How many pixels a meter is at the given world position.
+    pub fn pixels_per_meter(&self, p: Position) -> Option<f32> {
+        let depth = (p - self.pos).dot(self.rot.forward());
+        if depth < NEAR_PLANE {
+            return None;
+        }
+        Some(self.focal_length() / depth)
+    }
+}
+
+pub struct OverlaySettings {
+    pub enabled: bool,
+    pub fov: f32,
+    pub show_labels: bool,
+    pub show_cones: bool,
+    pub marker_size: f32,
+}
+
+impl OverlaySettings {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            fov: 60.0,
+            show_labels: true,
+            show_cones: true,
+            marker_size: 8.0,
+        }
+    }
+
+    pub fn handle_ui(&mut self, ui: &imgui::Ui) {
+        ui.checkbox("Show light markers", &mut self.enabled);
+        if !self.enabled {
+            return;
+        }
+
+        ui.same_line();
+        ui.checkbox("Labels", &mut self.show_labels);
+        ui.same_line();
+        ui.checkbox("Cones", &mut self.show_cones);
+        ui.slider_config("Camera FOV", 20.0, 120.0)
+            .build(&mut self.fov);
+        ui.slider_config("Marker size", 2.0, 30.0)
+            .build(&mut self.marker_size);
+    }
+
+    pub fn projection(&self, pos: Position, rot: RotationMatrix, ui: &imgui::Ui) -> Projection {
+        Projection {
+            pos,
+            rot,
+            fov: self.fov,
+            screen: ui.io().display_size,
+        }
+    }
+
+    /// Draws a marker for every light in the game's view. Returns the index of the light whose
+    /// marker got clicked, if any.
+    pub fn draw(
+        &self,
+        ui: &imgui::Ui,
+        projection: &Projection,
+        lights: &mut [LightContainer],
+        master: &MasterControls,
+        highlighted: Option<usize>,
+    ) -> Option<usize> {
+        if !self.enabled {
+            return None;
+        }
+
+        let draw_list = ui.get_background_draw_list();
+        let mouse = ui.io().mouse_pos;
+        let mut clicked: Option<(usize, f32)> = None;
+
+        for (i, light) in lights.iter_mut().enumerate() {
+            let (pos, rot) = light.get_pos_rot();
+            let Some(center) = projection.project(pos) else {
+                continue;
+            };
+
+            let is_highlighted = highlighted == Some(light.id);
+            let outline = if is_highlighted {
+                [1.0, 0.85, 0.0, 1.0]
+            } else {
+                [1.0, 1.0, 1.0, 0.8]
+            };
+
+            if self.show_cones {
+                if let LightType::SpotLight(spl) = &light.light {
+                    let radius = master.apply_radius(light.radius);
+                    let cone = spot_cone(pos, rot, radius, spl.outer_angle);
+                    let rim: Vec<[f32; 2]> =
+                        cone.iter().filter_map(|p| projection.project(*p)).collect();
+                    if rim.len() == cone.len() {
+                        for p in rim.iter().step_by(CONE_SEGMENTS / 4) {
+                            draw_list.add_line(center, *p, outline).build();
+                        }
+                        draw_list.add_polyline(rim, outline).build();
+                    }
+                }
+            }
+
+            let mut color = light.color;
+            color[3] = 1.0;
+            draw_list
+                .add_circle(center, self.marker_size, color)
+                .filled(true)
+                .build();
+            draw_list
+                .add_circle(center, self.marker_size, outline)
+                .thickness(if is_highlighted { 3.0 } else { 1.5 })
+                .build();
+
+            if self.show_labels {
+                let label_pos = [center[0] + self.marker_size + 4.0, center[1] - 7.0];
+                draw_list.add_text(label_pos, outline, &light.name);
+            }
+
+            let (dx, dy) = (mouse[0] - center[0], mouse[1] - center[1]);
+            let dist = (dx * dx + dy * dy).sqrt();
+            if dist <= self.marker_size + 2.0 && !matches!(clicked, Some((_, d)) if d <= dist) {
+                clicked = Some((i, dist));
+            }
+        }
+
+        if ui.io().want_capture_mouse || !ui.is_mouse_clicked(MouseButton::Left) {
+            return None;
+        }
+
+        clicked.map(|(i, _)| i)
+    }
+}
+
+/// Points of the rim of the spotlight's cone, closed so they can be drawn as a polyline.
+fn spot_cone(pos: Position, rot: RotationMatrix, length: f32, outer_angle: f32) -> Vec<Position> {
+    let half_angle = (outer_angle * 0.5).to_radians().min(1.5);
+    let rim_radius = length * half_angle.tan();
+    let center = pos + rot.forward() * length;
+
+    (0..=CONE_SEGMENTS)
+        .map(|i| {
+            let t = (i as f32) / (CONE_SEGMENTS as f32) * std::f32::consts::TAU;
+            center + rot.right() * (rim_radius * t.cos()) + rot.up() * (rim_radius * t.sin())
+        })
+        .collect()
+}
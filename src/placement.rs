@@ -0,0 +1,106 @@
+use crate::definitions::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SpawnPlacement {
+    Camera,
+    InFrontOfCamera,
+    Player,
+    PreviousLight,
+}
+
+impl SpawnPlacement {
+    const ALL: [Self; 4] = [
+        Self::Camera,
+        Self::InFrontOfCamera,
+        Self::Player,
+        Self::PreviousLight,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Camera => "At the camera",
+            Self::InFrontOfCamera => "In front of the camera",
+            Self::Player => "At the player",
+            Self::PreviousLight => "At the last selected light",
+        }
+    }
+}
+
+/// Where new lights get spawned. Spawning right at the camera puts the light inside the near
+/// plane, so most of the time one of the other options is what you want.
+pub struct PlacementOptions {
+    pub placement: SpawnPlacement,
+    /// Meters in front of the camera.
+    pub distance: f32,
+    /// Meters above the player's feet.
+    pub height: f32,
+    /// World space offset from the last selected light.
+    pub offset: [f32; 3],
+}
+
+impl PlacementOptions {
+    pub fn new() -> Self {
+        Self {
+            placement: SpawnPlacement::InFrontOfCamera,
+            distance: 2.0,
+            height: 1.5,
+            offset: [1.0, 0.0, 0.0],
+        }
+    }
+
+    pub fn handle_ui(&mut self, ui: &imgui::Ui) {
+        let mut current = SpawnPlacement::ALL
+            .iter()
+            .position(|p| *p == self.placement)
+            .unwrap_or(0);
+        if ui.combo("Spawn", &mut current, &SpawnPlacement::ALL, |p| {
+            p.label().into()
+        }) {
+            self.placement = SpawnPlacement::ALL[current];
+        }
+
+        match self.placement {
+            SpawnPlacement::Camera => (),
+            SpawnPlacement::InFrontOfCamera => {
+                ui.slider_config("Distance", 0.1, 50.0)
+                    .build(&mut self.distance);
+            }
+            SpawnPlacement::Player => {
+                ui.slider_config("Height", -5.0, 10.0)
+                    .build(&mut self.height);
+            }
+            SpawnPlacement::PreviousLight => {
+                imgui::Drag::new("Offset")
+                    .speed(0.05)
+                    .build_array(ui, &mut self.offset);
+            }
+        }
+    }
+
+    /// Computes where the light should be spawned. The rotation is always the camera's one so
+    /// spotlights point where we're looking, except when spawning next to another light, in which
+    /// case we copy its rotation. Returns None when the chosen reference isn't available.
+    pub fn resolve(
+        &self,
+        camera: (Position, RotationMatrix),
+        player: Option<Position>,
+        previous: Option<(Position, RotationMatrix)>,
+    ) -> Option<(Position, RotationMatrix)> {
+        let (camera_pos, camera_rot) = camera;
+        match self.placement {
+            SpawnPlacement::Camera => Some(camera),
+            SpawnPlacement::InFrontOfCamera => {
+                let pos = camera_pos + camera_rot.forward() * self.distance;
+                Some((pos, camera_rot))
+            }
+            SpawnPlacement::Player => {
+                let pos = player? + Position::new(0.0, 0.0, self.height);
+                Some((pos, camera_rot))
+            }
+            SpawnPlacement::PreviousLight => {
+                let (pos, rot) = previous?;
+                Some((pos + self.offset.into(), rot))
+            }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use std::fmt::Write;
+
+use anyhow::{Context, Result};
+
+use crate::definitions::*;
+use crate::fields::FieldId;
+use crate::math::kelvin_to_rgb;
+use crate::snapshot::*;
+
+pub const PRESETS_FILE: &str = "litcher_presets.txt";
+
+pub const PRESET_HEADER: &str = "preset";
+
+/// A named set of light settings to spawn new lights from.
+#[derive(Clone)]
+pub struct LightPreset {
+    pub name: String,
+    pub settings: LightSnapshot,
+}
+
+impl LightPreset {
+    fn write(&self, out: &mut String) {
+        write_header(out, PRESET_HEADER);
+        let _ = writeln!(out, "name = {}", self.name);
+        self.settings.write_fields(out);
+    }
+
+    fn from_fields(fields: &[(&str, &str)]) -> Result<Self> {
+        let name = fields
+            .iter()
+            .find(|(key, _)| *key == "name")
+            .context("Preset without name")?
+            .1
+            .to_string();
+
+        Ok(Self {
+            name,
+            settings: LightSnapshot::from_fields(fields)?,
+        })
+    }
+}
+
+fn starter_preset(
+    name: &str,
+    kind: LightKind,
+    kelvin: f32,
+    brightness: f32,
+    radius: f32,
+) -> LightPreset {
+    let mut settings = LightSnapshot::new(kind);
+    settings.color = kelvin_to_rgb(kelvin);
+    settings.set(FieldId::Brightness, brightness);
+    settings.set(FieldId::Radius, radius);
+    LightPreset {
+        name: name.to_string(),
+        settings,
+    }
+}
+
+/// The presets we ship, used when there's no library file yet.
+fn starter_presets() -> Vec<LightPreset> {
+    let mut candle = starter_preset("Candle", LightKind::PointLight, 1900.0, 40.0, 2.0);
+    candle.settings.set(FieldId::ShadowCastingMode, 2.0);
+
+    let torch = starter_preset("Torch", LightKind::PointLight, 2200.0, 400.0, 7.0);
+    let lantern = starter_preset("Lantern", LightKind::PointLight, 2700.0, 150.0, 4.0);
+
+    let mut moonlight = starter_preset("Moonlight fill", LightKind::SpotLight, 9000.0, 150.0, 30.0);
+    moonlight.settings.set(FieldId::InnerAngle, 60.0);
+    moonlight.settings.set(FieldId::OuterAngle, 80.0);
+    moonlight.settings.set(FieldId::Softness, 10.0);
+    moonlight.settings.set(FieldId::ShadowCastingMode, 0.0);
+
+    let mut window = starter_preset(
+        "Window daylight",
+        LightKind::SpotLight,
+        6500.0,
+        2000.0,
+        15.0,
+    );
+    window.settings.set(FieldId::InnerAngle, 40.0);
+    window.settings.set(FieldId::OuterAngle, 70.0);
+    window.settings.set(FieldId::Softness, 6.0);
+
+    let mut key = starter_preset("Portrait key", LightKind::SpotLight, 5600.0, 1000.0, 6.0);
+    key.settings.set(FieldId::InnerAngle, 20.0);
+    key.settings.set(FieldId::OuterAngle, 35.0);
+
+    vec![candle, torch, lantern, moonlight, window, key]
+}
+
+/// Things the user asked for in the presets UI that need the rest of the context.
+pub enum PresetEvent {
+    /// Spawn a light with the settings of the preset at the given index.
+    Spawn(usize),
+    /// Save the last selected light as a new preset.
+    SaveSelected,
+}
+
+pub struct PresetLibrary {
+    pub presets: Vec<LightPreset>,
+    pub current: usize,
+    pub new_name: String,
+    pub status: String,
+}
+
+impl PresetLibrary {
+    /// Reads the library file, falling back to the starter presets if it doesn't exist yet.
+    pub fn new() -> Self {
+        let (presets, status) = load_or(PRESETS_FILE, Self::parse, starter_presets);
+
+        Self {
+            presets,
+            current: 0,
+            new_name: String::new(),
+            status,
+        }
+    }
+
+    fn parse(text: &str) -> Result<Vec<LightPreset>> {
+        parse_blocks(text)?
+            .iter()
+            .filter(|block| block.name == PRESET_HEADER)
+            .map(|block| LightPreset::from_fields(&block.fields))
+            .collect()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let mut out = String::new();
+        for preset in self.presets.iter() {
+            preset.write(&mut out);
+            out.push('\n');
+        }
+
+        save_data(PRESETS_FILE, &out)
+    }
+
+    /// Adds the preset to the library, replacing the one with the same name if any, and saves
+    /// the library to disk.
+    pub fn add(&mut self, preset: LightPreset) -> Result<()> {
+        match self.presets.iter().position(|p| p.name == preset.name) {
+            Some(ix) => {
+                self.presets[ix] = preset;
+                self.current = ix;
+            }
+            None => {
+                self.presets.push(preset);
+                self.current = self.presets.len() - 1;
+            }
+        }
+
+        self.save()
+    }
+
+    pub fn handle_ui(&mut self, ui: &imgui::Ui) -> Option<PresetEvent> {
+        let mut event = None;
+
+        if self.presets.is_empty() {
+            ui.text("No presets in the library");
+        } else {
+            self.current = self.current.min(self.presets.len() - 1);
+            let presets = &self.presets;
+            let indices: Vec<usize> = (0..presets.len()).collect();
+            ui.combo("Preset", &mut self.current, &indices, |&i| {
+                format!("{} ({})", presets[i].name, presets[i].settings.kind.name()).into()
+            });
+
+            if ui.button("Spawn from preset") {
+                event = Some(PresetEvent::Spawn(self.current));
+            }
+            ui.same_line();
+            if ui.button("Delete preset") {
+                self.presets.remove(self.current);
+                self.status = match self.save() {
+                    Ok(()) => String::new(),
+                    Err(e) => format!("Error: {:#}", e),
+                };
+            }
+        }
+
+        ui.set_next_item_width(150.0);
+        ui.input_text("##preset_name", &mut self.new_name)
+            .hint("Preset name")
+            .build();
+        ui.same_line();
+        if ui.button("Save selected light as preset") {
+            event = Some(PresetEvent::SaveSelected);
+        }
+
+        if ui.button("Restore starter presets") {
+            for preset in starter_presets() {
+                if !self.presets.iter().any(|p| p.name == preset.name) {
+                    self.presets.push(preset);
+                }
+            }
+            self.status = match self.save() {
+                Ok(()) => String::new(),
+                Err(e) => format!("Error: {:#}", e),
+            };
+        }
+
+        if !self.status.is_empty() {
+            ui.text_wrapped(&self.status);
+        }
+
+        event
+    }
+}
@@ -0,0 +1,223 @@
+use crate::definitions::*;
+use crate::math::kelvin_to_rgb;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RigKind {
+    ThreePoint,
+    Rembrandt,
+    Split,
+    RimOnly,
+    Butterfly,
+}
+
+impl RigKind {
+    const ALL: [Self; 5] = [
+        Self::ThreePoint,
+        Self::Rembrandt,
+        Self::Split,
+        Self::RimOnly,
+        Self::Butterfly,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::ThreePoint => "Three-point",
+            Self::Rembrandt => "Rembrandt",
+            Self::Split => "Split",
+            Self::RimOnly => "Rim only",
+            Self::Butterfly => "Butterfly",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RigTarget {
+    Player,
+    CameraFocus,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum RigRole {
+    Key,
+    Fill,
+    Back,
+}
+
+/// One light of a rig, placed around the subject. The azimuth is in degrees around the subject,
+/// 0 being the camera side, and the elevation is in degrees above the subject.
+struct RigLight {
+    name: &'static str,
+    role: RigRole,
+    azimuth: f32,
+    elevation: f32,
+}
+
+const fn rig_light(name: &'static str, role: RigRole, azimuth: f32, elevation: f32) -> RigLight {
+    RigLight {
+        name,
+        role,
+        azimuth,
+        elevation,
+    }
+}
+
+impl RigKind {
+    fn lights(&self) -> &'static [RigLight] {
+        use RigRole::*;
+        match self {
+            Self::ThreePoint => &[
+                rig_light("Key", Key, 45.0, 30.0),
+                rig_light("Fill", Fill, -45.0, 10.0),
+                rig_light("Back", Back, 160.0, 35.0),
+            ],
+            Self::Rembrandt => &[
+                rig_light("Key", Key, 45.0, 45.0),
+                rig_light("Fill", Fill, -60.0, 0.0),
+            ],
+            Self::Split => &[rig_light("Key", Key, 90.0, 0.0)],
+            Self::RimOnly => &[
+                rig_light("Rim left", Back, 135.0, 20.0),
+                rig_light("Rim right", Back, -135.0, 20.0),
+            ],
+            Self::Butterfly => &[
+                rig_light("Key", Key, 0.0, 45.0),
+                rig_light("Fill", Fill, 0.0, -20.0),
+            ],
+        }
+    }
+}
+
+/// Where one of the rig's spotlights goes and how bright it is.
+pub struct RigPlacement {
+    pub name: String,
+    pub pos: Position,
+    pub rot: RotationMatrix,
+    pub brightness: f32,
+    pub radius: f32,
+    pub color: [f32; 4],
+}
+
+/// Classic photography lighting setups, spawned as spotlights aimed at the player or at a point
+/// in front of the camera.
+pub struct RigTool {
+    pub kind: RigKind,
+    pub target: RigTarget,
+    /// Meters from the subject to each light.
+    pub distance: f32,
+    /// Height of the subject above the player's feet (i.e. the face).
+    pub height: f32,
+    /// Meters in front of the camera when aiming at the camera's focus point.
+    pub focus_distance: f32,
+    pub key_brightness: f32,
+    /// How many times brighter the key light is than the fill.
+    pub key_to_fill: f32,
+    /// Kelvin.
+    pub color_temperature: f32,
+}
+
+impl RigTool {
+    pub fn new() -> Self {
+        Self {
+            kind: RigKind::ThreePoint,
+            target: RigTarget::Player,
+            distance: 2.5,
+            height: 1.7,
+            focus_distance: 3.0,
+            key_brightness: 1000.0,
+            key_to_fill: 2.0,
+            color_temperature: 5600.0,
+        }
+    }
+
+    /// Returns true when the user asked to spawn the rig.
+    pub fn handle_ui(&mut self, ui: &imgui::Ui) -> bool {
+        let mut current = RigKind::ALL
+            .iter()
+            .position(|k| *k == self.kind)
+            .unwrap_or(0);
+        if ui.combo("Rig", &mut current, &RigKind::ALL, |k| k.label().into()) {
+            self.kind = RigKind::ALL[current];
+        }
+
+        ui.radio_button("Around the player", &mut self.target, RigTarget::Player);
+        ui.same_line();
+        ui.radio_button("Camera focus", &mut self.target, RigTarget::CameraFocus);
+        match self.target {
+            RigTarget::Player => {
+                ui.slider_config("Subject height", 0.0, 3.0)
+                    .build(&mut self.height);
+            }
+            RigTarget::CameraFocus => {
+                ui.slider_config("Focus distance", 0.5, 30.0)
+                    .build(&mut self.focus_distance);
+            }
+        }
+
+        ui.slider_config("Light distance", 0.5, 20.0)
+            .build(&mut self.distance);
+        imgui::Drag::new("Key brightness")
+            .range(0.1, 100000.0)
+            .speed(1.0)
+            .build(ui, &mut self.key_brightness);
+        ui.slider_config("Key to fill ratio", 1.0, 16.0)
+            .build(&mut self.key_to_fill);
+        ui.slider_config("Color temperature (K)", 1500.0, 12000.0)
+            .build(&mut self.color_temperature);
+
+        ui.button("Spawn rig")
+    }
+
+    /// The point the rig lights up.
+    pub fn subject(
+        &self,
+        camera: (Position, RotationMatrix),
+        player: Option<Position>,
+    ) -> Option<Position> {
+        let (camera_pos, camera_rot) = camera;
+        match self.target {
+            RigTarget::Player => Some(player? + Position::new(0.0, 0.0, self.height)),
+            RigTarget::CameraFocus => Some(camera_pos + camera_rot.forward() * self.focus_distance),
+        }
+    }
+
+    pub fn placements(&self, subject: Position, camera_pos: Position) -> Vec<RigPlacement> {
+        // Horizontal direction from the subject to the camera, everything is placed relative to
+        // it so the rig looks the same from wherever we're looking.
+        let mut to_camera = camera_pos - subject;
+        to_camera.z = 0.0;
+        let to_camera = if to_camera.length() > 1e-3 {
+            to_camera.normalized()
+        } else {
+            Position::new(0.0, -1.0, 0.0)
+        };
+        let up = Position::new(0.0, 0.0, 1.0);
+        let color = kelvin_to_rgb(self.color_temperature);
+
+        self.kind
+            .lights()
+            .iter()
+            .map(|light| {
+                let horizontal = to_camera.rotated(up, light.azimuth.to_radians());
+                let elevation = light.elevation.to_radians();
+                let dir = horizontal * elevation.cos() + up * elevation.sin();
+                let pos = subject + dir * self.distance;
+
+                let brightness = match light.role {
+                    RigRole::Key => self.key_brightness,
+                    RigRole::Fill => self.key_brightness / self.key_to_fill,
+                    RigRole::Back => self.key_brightness * 0.75,
+                };
+
+                RigPlacement {
+                    name: format!("{} {}", self.kind.label(), light.name),
+                    pos,
+                    rot: RotationMatrix::look_at(subject - pos),
+                    brightness,
+                    // Enough to reach a bit past the subject.
+                    radius: self.distance * 1.5,
+                    color,
+                }
+            })
+            .collect()
+    }
+}
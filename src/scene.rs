@@ -0,0 +1,127 @@
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use crate::area::*;
+use crate::definitions::*;
+use crate::exposure::*;
+use crate::snapshot::*;
+
+/// Folder the scenes get saved in, one file per scene.
+pub const SCENES_DIR: &str = "litcher_scenes";
+
+/// A light as it's stored in a scene file: the same block as the clipboard one plus where the
+/// light is and how the user named it.
+pub struct SceneLight {
+    pub name: String,
+    pub group: Option<String>,
+    pub pos: Position,
+    pub rot: RotationMatrix,
+    pub settings: LightSnapshot,
+}
+
+impl SceneLight {
+    fn write(&self, out: &mut String) {
+        let [x, y, z]: [f32; 3] = self.pos.into();
+        let rot: [f32; 12] = self.rot.into();
+        let rot: Vec<String> = rot.iter().map(|v| v.to_string()).collect();
+
+        write_header(out, LIGHT_HEADER);
+        let _ = writeln!(out, "name = {}", self.name);
+        if let Some(group) = &self.group {
+            let _ = writeln!(out, "group = {}", group);
+        }
+        let _ = writeln!(out, "position = {} {} {}", x, y, z);
+        let _ = writeln!(out, "rotation = {}", rot.join(" "));
+        self.settings.write_fields(out);
+    }
+
+    fn from_fields(fields: &[(&str, &str)]) -> Result<Self> {
+        let settings = LightSnapshot::from_fields(fields)?;
+        let mut name = String::new();
+        let mut group = None;
+        let mut pos: Option<Position> = None;
+        let mut rot: Option<RotationMatrix> = None;
+        for (key, value) in fields {
+            match *key {
+                "name" => name = value.to_string(),
+                "group" => group = Some(value.to_string()),
+                "position" => pos = Some(parse_array::<3>(value)?.into()),
+                "rotation" => rot = Some(parse_array::<12>(value)?.into()),
+                _ => (),
+            }
+        }
+
+        Ok(Self {
+            name,
+            group,
+            pos: pos.context("Light without position")?,
+            rot: rot.context("Light without rotation")?,
+            settings,
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct Scene {
+    pub lights: Vec<SceneLight>,
+    pub areas: Vec<AreaLight>,
+    pub exposure: Option<ExposureSettings>,
+}
+
+impl Scene {
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        if let Some(exposure) = &self.exposure {
+            exposure.write(&mut out);
+            out.push('\n');
+        }
+
+        for area in self.areas.iter() {
+            area.write(&mut out);
+            out.push('\n');
+        }
+
+        for light in self.lights.iter() {
+            light.write(&mut out);
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn from_text(text: &str) -> Result<Self> {
+        let mut scene = Self::default();
+        for block in parse_blocks(text)? {
+            match block.name {
+                LIGHT_HEADER => scene.lights.push(SceneLight::from_fields(&block.fields)?),
+                AREA_HEADER => scene.areas.push(AreaLight::from_fields(&block.fields)?),
+                EXPOSURE_HEADER => {
+                    scene.exposure = Some(ExposureSettings::from_fields(&block.fields)?)
+                }
+                _ => (),
+            }
+        }
+
+        Ok(scene)
+    }
+
+    fn path(name: &str) -> PathBuf {
+        data_path(SCENES_DIR).join(format!("{}.txt", name))
+    }
+
+    pub fn save(&self, name: &str) -> Result<PathBuf> {
+        std::fs::create_dir_all(data_path(SCENES_DIR)).context("Couldn't create the scenes folder")?;
+        let path = Self::path(name);
+        std::fs::write(&path, self.to_text())
+            .with_context(|| format!("Couldn't write {}", path.display()))?;
+        Ok(path)
+    }
+
+    pub fn load(name: &str) -> Result<Self> {
+        let path = Self::path(name);
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("Couldn't read {}", path.display()))?;
+        Self::from_text(&text)
+    }
+}
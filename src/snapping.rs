@@ -0,0 +1,82 @@
+use crate::definitions::*;
+
+/// Optional snapping of positions to a world grid and of rotations to angle increments, for
+/// layouts that need exact spacing.
+pub struct Snapping {
+    pub grid_enabled: bool,
+    /// Meters between grid lines.
+    pub grid_size: f32,
+    pub angle_enabled: bool,
+    /// Degrees.
+    pub angle_step: f32,
+}
+
+fn snap(value: f32, step: f32) -> f32 {
+    (value / step).round() * step
+}
+
+impl Snapping {
+    pub fn new() -> Self {
+        Self {
+            grid_enabled: false,
+            grid_size: 0.5,
+            angle_enabled: false,
+            angle_step: 15.0,
+        }
+    }
+
+    pub fn handle_ui(&mut self, ui: &imgui::Ui) {
+        ui.checkbox("Snap to grid", &mut self.grid_enabled);
+        ui.same_line();
+        ui.set_next_item_width(100.0);
+        ui.input_float("Grid (m)", &mut self.grid_size).build();
+        self.grid_size = self.grid_size.max(0.01);
+
+        ui.checkbox("Snap angles", &mut self.angle_enabled);
+        ui.same_line();
+        ui.set_next_item_width(100.0);
+        ui.input_float("Step (deg)", &mut self.angle_step).build();
+        self.angle_step = self.angle_step.clamp(0.1, 180.0);
+    }
+
+    pub fn snap_position(&self, pos: Position) -> Position {
+        if !self.grid_enabled {
+            return pos;
+        }
+
+        Position::new(
+            snap(pos.x, self.grid_size),
+            snap(pos.y, self.grid_size),
+            snap(pos.z, self.grid_size),
+        )
+    }
+
+    pub fn snap_rotation(&self, rot: RotationMatrix) -> RotationMatrix {
+        if !self.angle_enabled {
+            return rot;
+        }
+
+        let step = self.angle_step.to_radians();
+        let [yaw, pitch, roll] = rot.to_euler();
+        RotationMatrix::from_euler(snap(yaw, step), snap(pitch, step), snap(roll, step))
+    }
+
+    /// Moves `pos` one grid cell along the world axis closest to `direction`. Moving along an
+    /// arbitrary direction and then snapping would mostly end up in the same cell.
+    pub fn step_along(&self, pos: Position, direction: Position) -> Position {
+        let (ax, ay, az) = (direction.x.abs(), direction.y.abs(), direction.z.abs());
+        if ax.max(ay).max(az) <= f32::EPSILON {
+            return pos;
+        }
+
+        let step = if ax >= ay && ax >= az {
+            Position::new(direction.x.signum(), 0.0, 0.0)
+        } else if ay >= az {
+            Position::new(0.0, direction.y.signum(), 0.0)
+        } else {
+            Position::new(0.0, 0.0, direction.z.signum())
+        };
+
+        self.snap_position(pos + step * self.grid_size)
+    }
+}
@@ -0,0 +1,299 @@
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::definitions::*;
+use crate::fields::*;
+
+/// Everything that defines how a light looks, without where it is. This is what gets copied
+/// between lights, and since it can be written to and read from plain text, it's also what we
+/// put on the system clipboard so people can share their settings.
+/// Besides the color and the on/off state, it holds one value per entry of `LIGHT_FIELDS`.
+/// Fields can be unset, those are left as they are when the snapshot gets applied.
+#[derive(Clone, Debug)]
+pub struct LightSnapshot {
+    pub kind: LightKind,
+    pub color: [f32; 4],
+    pub enabled: bool,
+    values: Vec<Option<f32>>,
+}
+
+pub const LIGHT_HEADER: &str = "light";
+/// Value written for fields the snapshot leaves to the game, so they stay unset when the text
+/// is read back instead of picking up the field's `default`.
+const UNSET: &str = "unset";
+
+impl LightSnapshot {
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        write_header(&mut out, LIGHT_HEADER);
+        self.write_fields(&mut out);
+        out
+    }
+
+    /// Appends the snapshot as `key = value` lines.
+    pub fn write_fields(&self, out: &mut String) {
+        let [r, g, b, a] = self.color;
+        let _ = writeln!(out, "kind = {}", self.kind.name());
+        let _ = writeln!(out, "color = {} {} {} {}", r, g, b, a);
+        let _ = writeln!(out, "enabled = {}", self.enabled);
+        for (field, value) in LIGHT_FIELDS.iter().zip(self.values.iter()) {
+            if !field.applies_to(self.kind) {
+                continue;
+            }
+
+            match value {
+                Some(value) => {
+                    let _ = writeln!(out, "{} = {}", field.name, field.format(*value));
+                }
+                None => {
+                    let _ = writeln!(out, "{} = {}", field.name, UNSET);
+                }
+            }
+        }
+    }
+
+    /// Parses a text with a single `[light]` block.
+    pub fn from_text(text: &str) -> Result<Self> {
+        match parse_blocks(text)?.as_slice() {
+            [block] if block.name == LIGHT_HEADER => Self::from_fields(&block.fields),
+            _ => bail!("Expected a single [{}] block", LIGHT_HEADER),
+        }
+    }
+
+    /// Builds the snapshot from the fields of a block. Unknown keys are ignored so newer
+    /// versions can still share settings with older ones, or so other things (like the scene)
+    /// can store their own fields in the same block.
+    pub fn from_fields(fields: &[(&str, &str)]) -> Result<Self> {
+        let kind = fields
+            .iter()
+            .find(|(key, _)| *key == "kind")
+            .context("No light kind found")?
+            .1;
+        let kind =
+            LightKind::from_name(kind).ok_or_else(|| anyhow!("Unknown light kind: {}", kind))?;
+
+        let mut snapshot = Self::new(kind);
+        for (key, value) in fields {
+            snapshot.read_field(key, value)?;
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Sets the field named `key`, clamped to its range, or unsets it. Returns false if we don't know about
+    /// that field.
+    pub fn read_field(&mut self, key: &str, value: &str) -> Result<bool> {
+        match key {
+            "color" => self.color = parse_array(value)?,
+            "enabled" => self.enabled = parse(value)?,
+            "kind" => (),
+            _ => match field_index(key) {
+                Some(ix) if value.trim() == UNSET => self.values[ix] = None,
+                Some(ix) => self.values[ix] = Some(LIGHT_FIELDS[ix].clamp(parse(value)?)),
+                None => return Ok(false),
+            },
+        }
+
+        Ok(true)
+    }
+
+    /// A snapshot that only sets the entries of `LIGHT_FIELDS` that have a `default`, the rest
+    /// are left to the game. This is what `SpawnDefaults` starts with, and fields missing from a
+    /// pasted text keep these.
+    pub fn new(kind: LightKind) -> Self {
+        Self {
+            kind,
+            color: [1.; 4],
+            enabled: true,
+            values: LIGHT_FIELDS.iter().map(|f| f.default).collect(),
+        }
+    }
+
+    /// Sets the field, clamped to its range.
+    pub fn set(&mut self, id: FieldId, value: f32) {
+        self.values[id as usize] = Some(field(id).clamp(value));
+    }
+
+    /// The fields that apply to this snapshot's kind and are set, alongside their values.
+    pub fn fields(&self) -> impl Iterator<Item = (&'static LightField, f32)> + '_ {
+        LIGHT_FIELDS
+            .iter()
+            .zip(self.values.iter())
+            .filter(|(field, _)| field.applies_to(self.kind))
+            .filter_map(|(field, value)| Some((field, (*value)?)))
+    }
+
+    /// Draws the widgets for every field of the snapshot, each one with a checkbox to choose
+    /// whether the snapshot sets it or leaves it to the game.
+    pub fn render_ui(&mut self, ui: &imgui::Ui) {
+        ui.color_edit4("Color", &mut self.color);
+        for (field, value) in LIGHT_FIELDS.iter().zip(self.values.iter_mut()) {
+            if !field.applies_to(self.kind) {
+                continue;
+            }
+
+            let _id = ui.push_id(field.name);
+            let mut set = value.is_some();
+            if ui.checkbox("##set", &mut set) {
+                *value = set.then(|| field.clamp(field.default.unwrap_or(field.min)));
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip_text("Unchecked fields keep the game's value");
+            }
+            ui.same_line();
+
+            match value {
+                Some(value) => {
+                    if field.render(ui, value, None) {
+                        *value = field.clamp(*value);
+                    }
+                }
+                None => ui.text_disabled(field.label),
+            }
+        }
+    }
+
+    /// Writes every field that's set straight to the game's memory, used on lights that don't
+    /// have a `LightContainer` yet.
+    ///
+    /// # Safety
+    /// `base` must point to the start of a light of the snapshot's kind.
+    pub unsafe fn write_memory(&self, base: *mut u8) {
+        for (field, value) in self.fields() {
+            field.write_memory(base, value);
+        }
+    }
+}
+
+/// Where our files (presets, spawn defaults, scenes...) go. The path is relative, so it ends up
+/// in the working directory of the game's process, which usually but not always is the folder of
+/// the game's executable.
+pub fn data_path(name: &str) -> PathBuf {
+    PathBuf::from(name)
+}
+
+/// Reads the file `name` and parses it with `parse`. When the file doesn't exist yet this gives
+/// `fallback` without complaining, any other failure gives `fallback` alongside the error to
+/// show to the user.
+pub fn load_or<T>(
+    name: &str,
+    parse: impl FnOnce(&str) -> Result<T>,
+    fallback: impl FnOnce() -> T,
+) -> (T, String) {
+    let path = data_path(name);
+    if !path.exists() {
+        return (fallback(), String::new());
+    }
+
+    let loaded = std::fs::read_to_string(&path)
+        .with_context(|| format!("Couldn't read {}", path.display()))
+        .and_then(|text| parse(&text));
+    match loaded {
+        Ok(value) => (value, String::new()),
+        Err(e) => (fallback(), format!("Error: {:#}", e)),
+    }
+}
+
+pub fn save_data(name: &str, text: &str) -> Result<()> {
+    let path = data_path(name);
+    std::fs::write(&path, text).with_context(|| format!("Couldn't write {}", path.display()))
+}
+
+/// A `[name]` header followed by its `key = value` lines.
+pub struct Block<'a> {
+    pub name: &'a str,
+    pub fields: Vec<(&'a str, &'a str)>,
+}
+
+pub fn write_header(out: &mut String, name: &str) {
+    let _ = writeln!(out, "[{}]", name);
+}
+
+/// Splits a text into its blocks. Empty lines and lines starting with `#` are skipped.
+pub fn parse_blocks(text: &str) -> Result<Vec<Block<'_>>> {
+    let mut blocks: Vec<Block> = Vec::new();
+    let lines = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'));
+
+    for line in lines {
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            blocks.push(Block {
+                name,
+                fields: Vec::new(),
+            });
+            continue;
+        }
+
+        let block = blocks
+            .last_mut()
+            .ok_or_else(|| anyhow!("`{}` is outside of a block", line))?;
+        block.fields.push(split_field(line)?);
+    }
+
+    Ok(blocks)
+}
+
+pub fn split_field(line: &str) -> Result<(&str, &str)> {
+    let (key, value) = line
+        .split_once('=')
+        .ok_or_else(|| anyhow!("Expected `key = value`, got `{}`", line))?;
+    Ok((key.trim(), value.trim()))
+}
+
+pub fn parse<T: FromStr>(value: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| anyhow!("Couldn't parse `{}`", value))
+}
+
+pub fn parse_array<const N: usize>(value: &str) -> Result<[f32; N]> {
+    let mut result = [0.0; N];
+    let mut values = value.split_whitespace();
+    for v in result.iter_mut() {
+        *v = parse(values.next().context("Not enough values")?)?;
+    }
+
+    if values.next().is_some() {
+        bail!("Too many values in `{}`", value);
+    }
+
+    Ok(result)
+}
+
+impl LightContainer {
+    pub fn snapshot(&mut self) -> LightSnapshot {
+        let mut snapshot = LightSnapshot::new(self.light.kind());
+        snapshot.color = self.color;
+        snapshot.enabled = self.enabled;
+        for (field, value) in LIGHT_FIELDS.iter().zip(snapshot.values.iter_mut()) {
+            if field.applies_to(snapshot.kind) {
+                *value = Some(field.read(self));
+            }
+        }
+
+        snapshot
+    }
+
+    /// Copies the settings into this light. The type specific fields only get copied when the
+    /// snapshot was taken from a light of the same type. The on/off state is left alone, since
+    /// pasting settings shouldn't turn lights on or off.
+    pub fn apply_snapshot(&mut self, snapshot: &LightSnapshot) {
+        self.color = snapshot.color;
+
+        let kind = self.light.kind();
+        for (field, value) in snapshot.fields() {
+            if field.applies_to(kind) {
+                field.write(self, value);
+            }
+        }
+
+        if let LightType::SpotLight(spl) = &mut self.light {
+            spl.fix_angles();
+        }
+    }
+}